@@ -1,9 +1,8 @@
-
 //! Procedural attribute macros used by the [satchel](https://docs.rs/satchel/latest/satchel/) crate to register tests and benchmarks.
 //!
 //! Do not use this crate directly, but through the re-exports in the [satchel](https://docs.rs/satchel/latest/satchel/) crate.
 use proc_macro::TokenStream;
-use quote::{format_ident, quote};
+use quote::{format_ident, quote, quote_spanned};
 use syn::spanned::Spanned;
 use syn::{ItemFn, LitStr, MetaNameValue, Path, parse_macro_input};
 
@@ -13,8 +12,28 @@ const DUP_EXPECTED: &str = "duplicate #[should_panic] expected message";
 const DUP_SHOULD_PANIC: &str = "duplicate #[should_panic] attribute";
 const DUP_IGNORE: &str = "duplicate #[ignore] attribute";
 const DUP_ATTR: &str = "duplicate attribute";
-const IGNORE_UNSUPPORTED: &str = "only #[ignore] and #[ignore = \"...\"] forms are supported";
+const IGNORE_UNSUPPORTED: &str = "only #[ignore], #[ignore = \"...\"] and #[ignore(<condition>, reason = \"...\")] forms are supported, where <condition> is one of if_env = \"VAR\", unless_env = \"VAR\" or if = predicate";
 const EXPECTED_STRING_AFTER_EQUALS: &str = "expected string literal after =";
+const SHOULD_PANIC_REQUIRES_UNIT: &str =
+    "#[should_panic] is only supported on functions returning ()";
+const TEST_CASE_EXPECTED_LIST: &str = "expected #[test_case(...)]";
+const DUP_TEST_CASE_LABEL: &str = "duplicate #[test_case] label";
+const TIMEOUT_UNSUPPORTED: &str = "timeout must be a positive duration such as \"500ms\", \"30s\", \"2m\", \"1h\" or a number of seconds";
+const RETRIES_UNSUPPORTED: &str = "retries must be a non-negative integer such as `retries = 3`";
+const SERIAL_GROUP_UNSUPPORTED: &str =
+    "serial_group must be a non-empty string literal such as `serial_group = \"db\"`";
+const DEPENDS_ON_UNSUPPORTED: &str = "depends_on must list tests by path or by string literal, such as `depends_on = [migrate_schema, \"add::small_numbers\"]`";
+const EXECUTOR_REQUIRES_ASYNC: &str = "executor = \"...\" is only supported on async functions";
+const CASE_ATTRIBUTE_UNSUPPORTED: &str = "expected a string literal, an identifier or `key = value` with a string, integer, float or bool literal in #[test(...)]";
+const TAG_UNSUPPORTED: &str = "expected tags(...) / #[tag(...)] listing identifiers or string literals made of letters, digits, `_`, `-`, `.` and `:`";
+const TAG_MISPLACED: &str = "#[tag(...)] must be placed below #[test], #[bench] or #[test_case]";
+const FIXTURE_PARAM: &str = "test parameters request fixtures by name and must be shared references, e.g. `database: &Database`";
+const BENCHER_REQUIRES_BENCH: &str = "only #[bench] functions can take a `&mut Bencher`";
+const BENCHER_SIGNATURE: &str =
+    "a #[bench] taking `&mut Bencher` must be a non-async function without other parameters";
+const FIXTURE_SIGNATURE: &str = "fixtures must be non-async, non-generic functions without parameters that return the value they provide";
+const FIXTURE_UNSUPPORTED: &str =
+    "expected #[fixture] or #[fixture(scope = \"test\" | \"module\" | \"run\")]";
 
 fn split_comma_separated_tokens(tokens: proc_macro2::TokenStream) -> Vec<proc_macro2::TokenStream> {
     use proc_macro2::{TokenStream as Ts, TokenTree};
@@ -41,13 +60,235 @@ fn split_comma_separated_tokens(tokens: proc_macro2::TokenStream) -> Vec<proc_ma
     segments
 }
 
-fn parse_case_attributes(attr_tokens: TokenStream) -> Result<Vec<LitStr>, syn::Error> {
+// Arguments accepted inside #[test(...)] / #[bench(...)]
+#[derive(Default)]
+struct CaseArgs {
+    // `::satchel::CaseAttribute` expressions, in source order
+    attributes: Vec<proc_macro2::TokenStream>,
+    // Keys of the `key = value` attributes, to reject duplicates
+    setting_keys: Vec<String>,
+    // From `tags(...)` and the `#[tag(...)]` attributes of the function
+    tags: Vec<LitStr>,
+    executor: Option<LitStr>,
+    // Timeout in milliseconds
+    timeout: Option<u64>,
+    retries: Option<u32>,
+    // From `serial_group = "..."`, or "default" for `serial`
+    serial_group: Option<LitStr>,
+    // From `depends_on = [...]`: paths, checked to exist at compile time, or string literals
+    depends_on: Option<Vec<syn::Expr>>,
+}
+
+fn expect_str_lit(value: syn::Expr) -> Result<LitStr, syn::Error> {
+    match value {
+        syn::Expr::Lit(syn::ExprLit {
+            lit: syn::Lit::Str(lit),
+            ..
+        }) => Ok(lit),
+        other => Err(syn::Error::new_spanned(other, EXPECTED_STRING_AFTER_EQUALS)),
+    }
+}
+
+fn set_once<T>(slot: &mut Option<T>, value: T, key: &Path) -> Result<(), syn::Error> {
+    if slot.is_some() {
+        return Err(syn::Error::new_spanned(key, DUP_ATTR));
+    }
+    *slot = Some(value);
+    Ok(())
+}
+
+/// Parses `timeout = "..."` values into milliseconds.
+/// Accepts `"<n>ms"`, `"<n>s"`, `"<n>m"`, `"<n>h"` or a bare integer number of seconds.
+fn parse_timeout(value: syn::Expr) -> Result<u64, syn::Error> {
+    if let syn::Expr::Lit(syn::ExprLit {
+        lit: syn::Lit::Int(seconds),
+        ..
+    }) = &value
+    {
+        return seconds
+            .base10_parse::<u64>()
+            .ok()
+            .and_then(|seconds| seconds.checked_mul(1000))
+            .filter(|millis| *millis > 0)
+            .ok_or_else(|| syn::Error::new_spanned(seconds, TIMEOUT_UNSUPPORTED));
+    }
+
+    let lit = expect_str_lit(value).map_err(|e| syn::Error::new(e.span(), TIMEOUT_UNSUPPORTED))?;
+    let text = lit.value();
+    let text = text.trim();
+    let split = text
+        .find(|c: char| !c.is_ascii_digit())
+        .unwrap_or(text.len());
+    let (amount, unit) = text.split_at(split);
+    let factor = match unit.trim() {
+        "ms" => 1,
+        "s" => 1000,
+        "m" => 60 * 1000,
+        "h" => 60 * 60 * 1000,
+        _ => return Err(syn::Error::new_spanned(&lit, TIMEOUT_UNSUPPORTED)),
+    };
+    amount
+        .parse::<u64>()
+        .ok()
+        .and_then(|amount| amount.checked_mul(factor))
+        .filter(|millis| *millis > 0)
+        .ok_or_else(|| syn::Error::new_spanned(&lit, TIMEOUT_UNSUPPORTED))
+}
+
+/// Parses `retries = N`, the number of extra attempts of a failing test.
+fn parse_retries(value: syn::Expr) -> Result<u32, syn::Error> {
+    match &value {
+        syn::Expr::Lit(syn::ExprLit {
+            lit: syn::Lit::Int(retries),
+            ..
+        }) => retries
+            .base10_parse::<u32>()
+            .map_err(|_| syn::Error::new_spanned(retries, RETRIES_UNSUPPORTED)),
+        _ => Err(syn::Error::new_spanned(value, RETRIES_UNSUPPORTED)),
+    }
+}
+
+/// Parses `depends_on = [...]`, the tests that must pass before this one. Paths name `#[test]`
+/// functions; string literals also name `#[test_case]` rows such as `"add::small_numbers"`.
+fn parse_depends_on(value: syn::Expr) -> Result<Vec<syn::Expr>, syn::Error> {
+    let unsupported = || syn::Error::new_spanned(&value, DEPENDS_ON_UNSUPPORTED);
+    let syn::Expr::Array(array) = &value else {
+        return Err(unsupported());
+    };
+    if array.elems.is_empty() {
+        return Err(unsupported());
+    }
+    for elem in &array.elems {
+        let supported = match elem {
+            syn::Expr::Path(path) => {
+                path.qself.is_none()
+                    && path.path.leading_colon.is_none()
+                    && path.path.segments.iter().all(|seg| seg.arguments.is_none())
+            }
+            syn::Expr::Lit(syn::ExprLit {
+                lit: syn::Lit::Str(name),
+                ..
+            }) => !name.value().is_empty(),
+            _ => false,
+        };
+        if !supported {
+            return Err(syn::Error::new_spanned(elem, DEPENDS_ON_UNSUPPORTED));
+        }
+    }
+    Ok(array.elems.iter().cloned().collect())
+}
+
+fn flag_attribute(key: &LitStr) -> proc_macro2::TokenStream {
+    quote! {
+        ::satchel::CaseAttribute { key: #key, value: ::satchel::AttributeValue::Flag }
+    }
+}
+
+/// Turns the literal of a `key = value` case attribute into an `AttributeValue` expression.
+fn attribute_value(value: &syn::Expr) -> Result<proc_macro2::TokenStream, syn::Error> {
+    let unsupported = || syn::Error::new_spanned(value, CASE_ATTRIBUTE_UNSUPPORTED);
+    let (negative, lit) = match value {
+        syn::Expr::Lit(syn::ExprLit { lit, .. }) => (false, lit),
+        syn::Expr::Unary(syn::ExprUnary {
+            op: syn::UnOp::Neg(_),
+            expr,
+            ..
+        }) => match &**expr {
+            syn::Expr::Lit(syn::ExprLit { lit, .. }) => (true, lit),
+            _ => return Err(unsupported()),
+        },
+        _ => return Err(unsupported()),
+    };
+    Ok(match lit {
+        syn::Lit::Str(text) if !negative => quote! { ::satchel::AttributeValue::Str(#text) },
+        syn::Lit::Bool(flag) if !negative => quote! { ::satchel::AttributeValue::Bool(#flag) },
+        syn::Lit::Int(int) => {
+            let digits = format!("{}{}", if negative { "-" } else { "" }, int.base10_digits());
+            let number = digits
+                .parse::<i64>()
+                .map_err(|e| syn::Error::new_spanned(int, e))?;
+            quote! { ::satchel::AttributeValue::Int(#number) }
+        }
+        syn::Lit::Float(float) => {
+            let digits = format!(
+                "{}{}",
+                if negative { "-" } else { "" },
+                float.base10_digits()
+            );
+            let number = digits
+                .parse::<f64>()
+                .map_err(|e| syn::Error::new_spanned(float, e))?;
+            quote! { ::satchel::AttributeValue::Float(#number) }
+        }
+        _ => return Err(unsupported()),
+    })
+}
+
+fn is_tag_char(c: char) -> bool {
+    c.is_ascii_alphanumeric() || matches!(c, '_' | '-' | '.' | ':')
+}
+
+/// Parses the list of `tags(...)` or `#[tag(...)]`: identifiers or string literals.
+fn parse_tag_list(
+    tokens: proc_macro2::TokenStream,
+    span: proc_macro2::Span,
+) -> Result<Vec<LitStr>, syn::Error> {
+    let segments = split_comma_separated_tokens(tokens);
+    if segments.is_empty() {
+        return Err(syn::Error::new(span, TAG_UNSUPPORTED));
+    }
+    segments
+        .into_iter()
+        .map(|segment| {
+            let tag = if let Ok(lit) = syn::parse2::<LitStr>(segment.clone()) {
+                lit
+            } else if let Ok(ident) = syn::parse2::<syn::Ident>(segment.clone()) {
+                LitStr::new(&ident.to_string(), ident.span())
+            } else {
+                return Err(syn::Error::new_spanned(segment, TAG_UNSUPPORTED));
+            };
+            let text = tag.value();
+            if text.is_empty() || !text.chars().all(is_tag_char) {
+                return Err(syn::Error::new_spanned(tag, TAG_UNSUPPORTED));
+            }
+            Ok(tag)
+        })
+        .collect()
+}
+
+// `#[tag]` or `#[satchel::tag]`; `tag` attributes of other crates are left alone.
+fn is_tag_attr(attr: &syn::Attribute) -> bool {
+    let path: Vec<String> = attr
+        .path()
+        .segments
+        .iter()
+        .map(|segment| segment.ident.to_string())
+        .collect();
+    path == ["tag"] || path == ["satchel", "tag"]
+}
+
+/// Collects the tags of every `#[tag(...)]` attribute of a function.
+fn parse_tag_attrs(attrs: &[syn::Attribute]) -> Result<Vec<LitStr>, syn::Error> {
+    let mut tags = Vec::new();
+    for attr in attrs.iter().filter(|attr| is_tag_attr(attr)) {
+        let syn::Meta::List(list) = &attr.meta else {
+            return Err(syn::Error::new_spanned(attr, TAG_UNSUPPORTED));
+        };
+        if list.tokens.is_empty() {
+            return Err(syn::Error::new_spanned(attr, TAG_UNSUPPORTED));
+        }
+        tags.extend(parse_tag_list(list.tokens.clone(), attr.span())?);
+    }
+    Ok(tags)
+}
+
+fn parse_case_attributes(attr_tokens: proc_macro2::TokenStream) -> Result<CaseArgs, syn::Error> {
+    let mut parsed = CaseArgs::default();
     if attr_tokens.is_empty() {
-        return Ok(Vec::new());
+        return Ok(parsed);
     }
 
-    let segments = split_comma_separated_tokens(attr_tokens.into());
-    let mut parsed = Vec::with_capacity(segments.len());
+    let segments = split_comma_separated_tokens(attr_tokens);
 
     for segment in segments {
         if segment.is_empty() {
@@ -55,30 +296,209 @@ fn parse_case_attributes(attr_tokens: TokenStream) -> Result<Vec<LitStr>, syn::E
         }
 
         if let Ok(lit) = syn::parse2::<LitStr>(segment.clone()) {
-            parsed.push(lit);
+            parsed.attributes.push(flag_attribute(&lit));
             continue;
         }
 
         if let Ok(path) = syn::parse2::<Path>(segment.clone()) {
+            if path.is_ident("serial") {
+                let group = LitStr::new("default", path.span());
+                set_once(&mut parsed.serial_group, group, &path)?;
+                continue;
+            }
             let value = path
                 .segments
                 .iter()
                 .map(|seg| seg.ident.to_string())
                 .collect::<Vec<_>>()
                 .join("::");
-            parsed.push(LitStr::new(&value, path.span()));
+            parsed
+                .attributes
+                .push(flag_attribute(&LitStr::new(&value, path.span())));
             continue;
         }
 
-        return Err(syn::Error::new_spanned(
-            segment,
-            "only string literals or bare identifiers are supported in #[test(...)]",
-        ));
+        if let Ok(list) = syn::parse2::<syn::MetaList>(segment.clone())
+            && list.path.is_ident("tags")
+        {
+            parsed
+                .tags
+                .extend(parse_tag_list(list.tokens, list.path.span())?);
+            continue;
+        }
+
+        if let Ok(MetaNameValue { path, value, .. }) = syn::parse2::<MetaNameValue>(segment.clone())
+        {
+            if path.is_ident("executor") {
+                set_once(&mut parsed.executor, expect_str_lit(value)?, &path)?;
+                continue;
+            }
+            if path.is_ident("timeout") {
+                set_once(&mut parsed.timeout, parse_timeout(value)?, &path)?;
+                continue;
+            }
+            if path.is_ident("retries") {
+                set_once(&mut parsed.retries, parse_retries(value)?, &path)?;
+                continue;
+            }
+            if path.is_ident("serial_group") {
+                let unsupported = syn::Error::new_spanned(&value, SERIAL_GROUP_UNSUPPORTED);
+                let group = expect_str_lit(value)
+                    .ok()
+                    .filter(|group| !group.value().is_empty())
+                    .ok_or(unsupported)?;
+                set_once(&mut parsed.serial_group, group, &path)?;
+                continue;
+            }
+            if path.is_ident("depends_on") {
+                set_once(&mut parsed.depends_on, parse_depends_on(value)?, &path)?;
+                continue;
+            }
+            let key = path
+                .segments
+                .iter()
+                .map(|seg| seg.ident.to_string())
+                .collect::<Vec<_>>()
+                .join("::");
+            if parsed.setting_keys.contains(&key) {
+                return Err(syn::Error::new_spanned(&path, DUP_ATTR));
+            }
+            let value = attribute_value(&value)?;
+            let key_lit = LitStr::new(&key, path.span());
+            parsed.attributes.push(quote! {
+                ::satchel::CaseAttribute { key: #key_lit, value: #value }
+            });
+            parsed.setting_keys.push(key);
+            continue;
+        }
+
+        return Err(syn::Error::new_spanned(segment, CASE_ATTRIBUTE_UNSUPPORTED));
     }
 
     Ok(parsed)
 }
 
+// One parameter set of a #[test_case(args => expected; label = "...", case attributes)] row
+struct TestCaseRow {
+    span: proc_macro2::Span,
+    args: Vec<syn::Expr>,
+    expected: Option<syn::Expr>,
+    label: Option<LitStr>,
+    case_args: CaseArgs,
+}
+
+// Splits `tokens` at the first top-level occurrence of `separator` (a one or two character
+// punctuation such as `;` or `=>`).
+fn split_once_at_punct(
+    tokens: proc_macro2::TokenStream,
+    separator: &str,
+) -> (proc_macro2::TokenStream, Option<proc_macro2::TokenStream>) {
+    use proc_macro2::{Spacing, TokenTree};
+
+    let tokens: Vec<TokenTree> = tokens.into_iter().collect();
+    let chars: Vec<char> = separator.chars().collect();
+    for index in 0..tokens.len() {
+        let matches =
+            chars
+                .iter()
+                .enumerate()
+                .all(|(offset, expected)| match tokens.get(index + offset) {
+                    Some(TokenTree::Punct(p)) => {
+                        p.as_char() == *expected
+                            && (offset + 1 == chars.len() || p.spacing() == Spacing::Joint)
+                    }
+                    _ => false,
+                });
+        if matches {
+            let head = tokens[..index].iter().cloned().collect();
+            let tail = tokens[index + chars.len()..].iter().cloned().collect();
+            return (head, Some(tail));
+        }
+    }
+    (tokens.into_iter().collect(), None)
+}
+
+impl TestCaseRow {
+    fn parse(
+        tokens: proc_macro2::TokenStream,
+        span: proc_macro2::Span,
+    ) -> Result<Self, syn::Error> {
+        use syn::parse::Parser;
+
+        let (values, meta) = split_once_at_punct(tokens, ";");
+        let (args, expected) = split_once_at_punct(values, "=>");
+        let args = syn::punctuated::Punctuated::<syn::Expr, syn::Token![,]>::parse_terminated
+            .parse2(args)?
+            .into_iter()
+            .collect();
+        let expected = expected.map(syn::parse2::<syn::Expr>).transpose()?;
+
+        let mut label = None;
+        let mut case_attribute_tokens = proc_macro2::TokenStream::new();
+        for segment in split_comma_separated_tokens(meta.unwrap_or_default()) {
+            if let Ok(MetaNameValue { path, value, .. }) =
+                syn::parse2::<MetaNameValue>(segment.clone())
+                && path.is_ident("label")
+            {
+                let syn::Expr::Lit(syn::ExprLit {
+                    lit: syn::Lit::Str(text),
+                    ..
+                }) = value
+                else {
+                    return Err(syn::Error::new_spanned(value, EXPECTED_STRING_AFTER_EQUALS));
+                };
+                if label.is_some() {
+                    return Err(syn::Error::new_spanned(path, DUP_ATTR));
+                }
+                label = Some(text);
+                continue;
+            }
+            case_attribute_tokens.extend(quote! { #segment, });
+        }
+
+        Ok(TestCaseRow {
+            span,
+            args,
+            expected,
+            label,
+            case_args: parse_case_attributes(case_attribute_tokens)?,
+        })
+    }
+
+    // Name suffix of the generated case: the sanitized label, or `case_<index>`.
+    fn case_name(&self, index: usize) -> Result<String, syn::Error> {
+        let Some(label) = &self.label else {
+            return Ok(format!("case_{}", index));
+        };
+        let mut name = String::new();
+        for c in label.value().chars() {
+            if c.is_alphanumeric() {
+                name.extend(c.to_lowercase());
+            } else if !name.is_empty() && !name.ends_with('_') {
+                name.push('_');
+            }
+        }
+        let name = name.trim_end_matches('_').to_string();
+        if name.is_empty() {
+            return Err(syn::Error::new_spanned(
+                label,
+                "#[test_case] label must contain letters or digits",
+            ));
+        }
+        Ok(name)
+    }
+}
+
+fn is_test_case_attr(attr: &syn::Attribute) -> bool {
+    let path: Vec<String> = attr
+        .path()
+        .segments
+        .iter()
+        .map(|segment| segment.ident.to_string())
+        .collect();
+    path == ["test_case"] || path == ["satchel", "test_case"]
+}
+
 // Helper that returns at most one attribute by name, or an error if duplicates are present.
 fn single_attr<'a>(
     attrs: &'a [syn::Attribute],
@@ -93,7 +513,7 @@ fn single_attr<'a>(
         };
         return Err(syn::Error::new_spanned(matches[1], msg));
     }
-    Ok(matches.get(0).copied())
+    Ok(matches.first().copied())
 }
 
 // Parsed state holder for #[should_panic(...)] list forms
@@ -116,27 +536,24 @@ impl ShouldPanicParseResult {
         let segments = split_comma_separated_tokens(list.tokens.clone());
         for segment in segments {
             // Try expected = "..." first to recover exact span for duplicate on the `expected` ident
-            if let Ok(meta) = syn::parse2::<syn::Meta>(segment.clone()) {
-                if let syn::Meta::NameValue(MetaNameValue {
-                    path,
-                    value:
-                        syn::Expr::Lit(syn::ExprLit {
-                            lit: syn::Lit::Str(s),
-                            ..
-                        }),
-                    ..
-                }) = meta
-                {
-                    if path.is_ident("expected") {
-                        if res.expected.is_some() {
-                            // Duplicate expected -> error on the `expected` identifier span
-                            res.errors.push(syn::Error::new(path.span(), DUP_EXPECTED));
-                        } else {
-                            res.expected = Some(s.value());
-                        }
-                        continue;
-                    }
+            if let Ok(syn::Meta::NameValue(MetaNameValue {
+                path,
+                value:
+                    syn::Expr::Lit(syn::ExprLit {
+                        lit: syn::Lit::Str(s),
+                        ..
+                    }),
+                ..
+            })) = syn::parse2::<syn::Meta>(segment.clone())
+                && path.is_ident("expected")
+            {
+                if res.expected.is_some() {
+                    // Duplicate expected -> error on the `expected` identifier span
+                    res.errors.push(syn::Error::new(path.span(), DUP_EXPECTED));
+                } else {
+                    res.expected = Some(s.value());
                 }
+                continue;
             }
 
             // Try positional string literal
@@ -233,9 +650,67 @@ fn parse_should_panic_attr(
     Ok(tokens)
 }
 
-/// Handles two forms:
+/// Parses the list of a conditional `#[ignore(...)]`: one of `if_env = "VAR"`,
+/// `unless_env = "VAR"` or `if = predicate`, optionally followed by `reason = "..."`.
+/// Returns the `reason` and `condition` expressions of the `Ignore`.
+fn parse_ignore_condition(
+    list: &syn::MetaList,
+) -> Result<(proc_macro2::TokenStream, proc_macro2::TokenStream), syn::Error> {
+    let unsupported =
+        |tokens: &dyn quote::ToTokens| syn::Error::new_spanned(tokens, IGNORE_UNSUPPORTED);
+    let mut reason: Option<LitStr> = None;
+    let mut condition: Option<proc_macro2::TokenStream> = None;
+    for segment in split_comma_separated_tokens(list.tokens.clone()) {
+        let (key, value) = split_once_at_punct(segment.clone(), "=");
+        let value = value.ok_or_else(|| unsupported(&segment))?;
+        // `if` is a keyword, so the key is compared as text rather than parsed as a path.
+        let parsed = match key.to_string().as_str() {
+            "reason" => {
+                let lit = syn::parse2::<LitStr>(value).map_err(|_| unsupported(&segment))?;
+                if reason.replace(lit).is_some() {
+                    return Err(syn::Error::new_spanned(&key, DUP_ATTR));
+                }
+                continue;
+            }
+            variant @ ("if_env" | "unless_env") => {
+                let name = syn::parse2::<LitStr>(value).map_err(|_| unsupported(&segment))?;
+                let variant = if variant == "if_env" {
+                    quote! { IfEnv }
+                } else {
+                    quote! { UnlessEnv }
+                };
+                quote! { ::satchel::IgnoreCondition::#variant(#name) }
+            }
+            "if" => {
+                let predicate = syn::parse2::<Path>(value).map_err(|_| unsupported(&segment))?;
+                let name = quote!(#predicate).to_string().replace(' ', "");
+                quote! {
+                    ::satchel::IgnoreCondition::If {
+                        name: #name,
+                        predicate: #predicate,
+                    }
+                }
+            }
+            _ => return Err(unsupported(&segment)),
+        };
+        if condition.replace(parsed).is_some() {
+            return Err(syn::Error::new_spanned(&key, DUP_ATTR));
+        }
+    }
+
+    let condition = condition.ok_or_else(|| unsupported(list))?;
+    let reason = match reason {
+        Some(reason) => quote! { ::core::option::Option::Some(#reason) },
+        None => quote! { ::core::option::Option::None },
+    };
+    Ok((reason, quote! { ::core::option::Option::Some(#condition) }))
+}
+
+/// Handles three forms:
 /// 1. `#[ignore]` - simple ignore without reason
 /// 2. `#[ignore = "..."]` - ignore with a reason string
+/// 3. `#[ignore(if_env = "...", reason = "...")]` - ignore if a condition holds when the run
+///    starts, see `parse_ignore_condition`
 fn parse_ignore_attr(attrs: &[syn::Attribute]) -> Result<proc_macro2::TokenStream, syn::Error> {
     let attr = match single_attr(attrs, "ignore") {
         Ok(Some(attr)) => attr,
@@ -243,35 +718,298 @@ fn parse_ignore_attr(attrs: &[syn::Attribute]) -> Result<proc_macro2::TokenStrea
         Err(e) => return Err(e),
     };
 
-    // Handle #[ignore = "reason"]
-    if let syn::Meta::NameValue(MetaNameValue {
-        value:
-            syn::Expr::Lit(syn::ExprLit {
-                lit: syn::Lit::Str(lit_str),
-                ..
-            }),
-        ..
-    }) = &attr.meta
+    let (reason, condition) = match &attr.meta {
+        // Handle #[ignore = "reason"]
+        syn::Meta::NameValue(MetaNameValue {
+            value:
+                syn::Expr::Lit(syn::ExprLit {
+                    lit: syn::Lit::Str(lit_str),
+                    ..
+                }),
+            ..
+        }) => {
+            let reason = lit_str.value();
+            (
+                quote! { ::core::option::Option::Some(#reason) },
+                quote! { ::core::option::Option::None },
+            )
+        }
+        // Handle #[ignore] without reason
+        syn::Meta::Path(_) => (
+            quote! { ::core::option::Option::None },
+            quote! { ::core::option::Option::None },
+        ),
+        // Handle #[ignore(if_env = "...", ...)]
+        syn::Meta::List(list) => parse_ignore_condition(list)?,
+        // Unsupported form - emit compile error
+        _ => return Err(syn::Error::new_spanned(attr, IGNORE_UNSUPPORTED)),
+    };
+    Ok(quote! {
+        ::core::option::Option::Some(::satchel::Ignore {
+            reason: #reason,
+            condition: #condition,
+        })
+    })
+}
+
+/// Returns the fixture names requested by the parameters of a test function.
+fn fixture_params(sig: &syn::Signature) -> Result<Vec<syn::Ident>, syn::Error> {
+    sig.inputs
+        .iter()
+        .map(|input| match input {
+            syn::FnArg::Typed(syn::PatType { pat, ty, .. }) => match (&**pat, &**ty) {
+                (
+                    syn::Pat::Ident(syn::PatIdent {
+                        ident,
+                        by_ref: None,
+                        mutability: None,
+                        subpat: None,
+                        ..
+                    }),
+                    syn::Type::Reference(syn::TypeReference {
+                        mutability: None, ..
+                    }),
+                ) => Ok(ident.clone()),
+                _ => Err(syn::Error::new_spanned(input, FIXTURE_PARAM)),
+            },
+            syn::FnArg::Receiver(_) => Err(syn::Error::new_spanned(input, FIXTURE_PARAM)),
+        })
+        .collect()
+}
+
+/// Returns the parameter of a benchmark written as `fn(&mut Bencher)`. It is recognized by the
+/// last segment of the type, so `&mut satchel::Bencher` and an imported `&mut Bencher` both work.
+fn bencher_param(sig: &syn::Signature) -> Option<&syn::FnArg> {
+    sig.inputs.iter().find(|input| {
+        let syn::FnArg::Typed(syn::PatType { ty, .. }) = input else {
+            return false;
+        };
+        let syn::Type::Reference(syn::TypeReference {
+            mutability: Some(_),
+            elem,
+            ..
+        }) = &**ty
+        else {
+            return false;
+        };
+        matches!(&**elem, syn::Type::Path(path)
+            if path.path.segments.last().is_some_and(|segment| segment.ident == "Bencher"))
+    })
+}
+
+/// Builds the `EntryPoint` for a function with the given signature.
+/// Functions with an explicit return type are wrapped so `Err` values become a `TestError`,
+/// and `async fn`s are wrapped into a boxed future for the harness' executor. Parameters are
+/// fixtures, looked up by the harness and passed in through `FixtureValues`, except for the
+/// `&mut Bencher` of a benchmark when `allows_bencher` is set.
+fn entry_point(
+    sig: &syn::Signature,
+    attrs: &[syn::Attribute],
+    allows_bencher: bool,
+) -> Result<proc_macro2::TokenStream, syn::Error> {
+    let fn_name = &sig.ident;
+    if !matches!(sig.output, syn::ReturnType::Default)
+        && let Some(attr) = single_attr(attrs, "should_panic")?
     {
-        let reason = lit_str.value();
+        return Err(syn::Error::new_spanned(attr, SHOULD_PANIC_REQUIRES_UNIT));
+    }
+
+    if let Some(bencher) = bencher_param(sig) {
+        if !allows_bencher {
+            return Err(syn::Error::new_spanned(bencher, BENCHER_REQUIRES_BENCH));
+        }
+        if sig.asyncness.is_some() || sig.inputs.len() != 1 {
+            return Err(syn::Error::new_spanned(sig, BENCHER_SIGNATURE));
+        }
         return Ok(quote! {
-            ::core::option::Option::Some(::satchel::Ignore {
-                reason: ::core::option::Option::Some(#reason),
+            ::satchel::EntryPoint::Bench(|bencher: &mut ::satchel::Bencher<'_>| {
+                ::satchel::TestReturn::into_test_result(#fn_name(bencher))
             })
         });
     }
 
-    // Handle #[ignore] without reason
-    if let syn::Meta::Path(_) = &attr.meta {
+    let fixtures = fixture_params(sig)?;
+    if !fixtures.is_empty() {
+        let handles = fixtures.iter().map(|name| {
+            let handle = fixture_handle(name);
+            quote_spanned! {name.span()=> #handle }
+        });
+        let indices = 0..fixtures.len();
+        if sig.asyncness.is_some() {
+            let locals: Vec<_> = (0..fixtures.len())
+                .map(|index| format_ident!("__fixture_{}", index))
+                .collect();
+            return Ok(quote! {
+                ::satchel::EntryPoint::AsyncFixtures(|fixtures: &::satchel::FixtureValues| {
+                    #( let #locals = fixtures.shared(#indices, #handles); )*
+                    ::satchel::into_test_future(async move { #fn_name(#( &*#locals ),*).await })
+                })
+            });
+        }
         return Ok(quote! {
-            ::core::option::Option::Some(::satchel::Ignore {
-                reason: ::core::option::Option::None,
+            ::satchel::EntryPoint::Fixtures(|fixtures: &::satchel::FixtureValues| {
+                ::satchel::TestReturn::into_test_result(
+                    #fn_name(#( fixtures.get(#indices, #handles) ),*)
+                )
             })
         });
     }
 
-    // Unsupported form - emit compile error
-    Err(syn::Error::new_spanned(attr, IGNORE_UNSUPPORTED))
+    if sig.asyncness.is_some() {
+        return Ok(quote! {
+            ::satchel::EntryPoint::Async(|| ::satchel::into_test_future(#fn_name()))
+        });
+    }
+    match &sig.output {
+        syn::ReturnType::Default => Ok(quote! { ::satchel::EntryPoint::Plain(#fn_name) }),
+        syn::ReturnType::Type(..) => Ok(quote! {
+            ::satchel::EntryPoint::Result(|| ::satchel::TestReturn::into_test_result(#fn_name()))
+        }),
+    }
+}
+
+// Everything needed to emit one `TestCase` into the distributed slice.
+struct Registration {
+    name: String,
+    static_name: proc_macro2::Ident,
+    // That of the test function, so that a dependent can name the static wherever it can name
+    // the function.
+    vis: syn::Visibility,
+    // Span whose line and column become the case's `SourceLocation`.
+    location_span: proc_macro2::Span,
+    kind: proc_macro2::TokenStream,
+    entry_point: proc_macro2::TokenStream,
+    is_async: bool,
+    should_panic: proc_macro2::TokenStream,
+    ignore: proc_macro2::TokenStream,
+    // Fixtures requested by the parameters of the test, see `fixture_params`.
+    fixtures: Vec<syn::Ident>,
+    case_args: CaseArgs,
+}
+
+impl Registration {
+    fn into_tokens(self) -> Result<proc_macro2::TokenStream, syn::Error> {
+        let Registration {
+            name,
+            static_name,
+            vis,
+            location_span,
+            kind,
+            entry_point,
+            is_async,
+            should_panic,
+            ignore,
+            fixtures,
+            case_args:
+                CaseArgs {
+                    attributes: case_attributes,
+                    executor,
+                    timeout,
+                    retries,
+                    serial_group,
+                    depends_on,
+                    tags,
+                    ..
+                },
+        } = self;
+
+        let location = quote_spanned! {location_span=>
+            ::satchel::SourceLocation {
+                file: ::core::file!(),
+                line: ::core::line!(),
+                column: ::core::column!(),
+            }
+        };
+        let executor = match executor {
+            Some(name) if !is_async => {
+                return Err(syn::Error::new_spanned(name, EXECUTOR_REQUIRES_ASYNC));
+            }
+            Some(name) => quote! { ::core::option::Option::Some(#name) },
+            None => quote! { ::core::option::Option::None },
+        };
+        let timeout = match timeout {
+            Some(millis) => quote! {
+                ::core::option::Option::Some(::core::time::Duration::from_millis(#millis))
+            },
+            None => quote! { ::core::option::Option::None },
+        };
+        let retries = retries.unwrap_or(0);
+        let serial_group = match serial_group {
+            Some(group) => quote! { ::core::option::Option::Some(#group) },
+            None => quote! { ::core::option::Option::None },
+        };
+        // A path must name a `#[test]` function, so a misspelled dependency or one on a plain
+        // function fails to compile: it is checked through the `static` registering the test. The
+        // harness checks string dependencies at startup.
+        let mut dependency_names = Vec::new();
+        let mut dependency_paths = Vec::new();
+        for dependency in depends_on.unwrap_or_default() {
+            match dependency {
+                syn::Expr::Path(path) => {
+                    let name = path
+                        .path
+                        .segments
+                        .iter()
+                        .map(|seg| seg.ident.to_string())
+                        .collect::<Vec<_>>()
+                        .join("::");
+                    dependency_names.push(LitStr::new(&name, path.span()));
+                    let mut path = path.path;
+                    let last = path.segments.last_mut().expect("paths have a segment");
+                    last.ident = format_ident!(
+                        "__SATCHEL_TEST_{}",
+                        last.ident.to_string().to_uppercase(),
+                        span = last.ident.span()
+                    );
+                    dependency_paths.push(path);
+                }
+                syn::Expr::Lit(syn::ExprLit {
+                    lit: syn::Lit::Str(name),
+                    ..
+                }) => dependency_names.push(name),
+                _ => unreachable!("parse_depends_on only accepts paths and string literals"),
+            }
+        }
+        let dependency_check = (!dependency_paths.is_empty()).then(|| {
+            quote! {
+                const _: () = { #( let _ = &#dependency_paths; )* };
+            }
+        });
+        let fixtures = fixtures.iter().map(|name| {
+            let handle = fixture_handle(name);
+            quote_spanned! {name.span()=> #handle.fixture() }
+        });
+        let mut unique_tags: Vec<String> = Vec::new();
+        for tag in &tags {
+            if !unique_tags.contains(&tag.value()) {
+                unique_tags.push(tag.value());
+            }
+        }
+
+        Ok(quote! {
+            #[linkme::distributed_slice(::satchel::test_harness::TESTS)]
+            #[doc(hidden)]
+            #vis static #static_name: ::satchel::TestCase = ::satchel::TestCase {
+                name: #name,
+                module_path: ::core::module_path!(),
+                location: #location,
+                kind: #kind,
+                test_fn: #entry_point,
+                should_panic: #should_panic,
+                ignore: #ignore,
+                case_attributes: &[ #( #case_attributes ),* ],
+                tags: &[ #( #unique_tags ),* ],
+                executor: #executor,
+                timeout: #timeout,
+                retries: #retries,
+                serial_group: #serial_group,
+                depends_on: &[ #( #dependency_names ),* ],
+                fixtures: &[ #( #fixtures ),* ],
+            };
+
+            #dependency_check
+        })
+    }
 }
 
 #[doc = "Please use this macro via the re-export in [satchel](https://docs.rs/satchel/latest/satchel/)."]
@@ -282,6 +1020,7 @@ pub fn test(attr: TokenStream, item: TokenStream) -> TokenStream {
         item,
         quote! {::satchel::TestKind::Unit },
         "__SATCHEL_TEST_",
+        false,
     )
 }
 
@@ -293,14 +1032,148 @@ pub fn bench(attr: TokenStream, item: TokenStream) -> TokenStream {
         item,
         quote! { ::satchel::TestKind::Benchmark },
         "__SATCHEL_BENCH_",
+        true,
+    )
+}
+
+#[doc = "Please use this macro via the re-export in [satchel](https://docs.rs/satchel/latest/satchel/)."]
+#[proc_macro_attribute]
+pub fn test_case(attr: TokenStream, item: TokenStream) -> TokenStream {
+    match expand_test_case(attr.into(), item) {
+        Ok(ts) => ts.into(),
+        Err(e) => e.into_compile_error().into(),
+    }
+}
+
+#[doc = "Please use this macro via the re-export in [satchel](https://docs.rs/satchel/latest/satchel/)."]
+#[proc_macro_attribute]
+pub fn tag(_attr: TokenStream, item: TokenStream) -> TokenStream {
+    // #[test] and friends consume #[tag] below them, so reaching this means it was misplaced.
+    let mut expanded: proc_macro2::TokenStream =
+        syn::Error::new(proc_macro2::Span::call_site(), TAG_MISPLACED).into_compile_error();
+    expanded.extend(proc_macro2::TokenStream::from(item));
+    expanded.into()
+}
+
+#[doc = "Please use this macro via the re-export in [satchel](https://docs.rs/satchel/latest/satchel/)."]
+#[proc_macro_attribute]
+pub fn fixture(attr: TokenStream, item: TokenStream) -> TokenStream {
+    match expand_fixture(attr.into(), item) {
+        Ok(ts) => ts.into(),
+        Err(e) => e.into_compile_error().into(),
+    }
+}
+
+/// Parses the arguments of `#[fixture(...)]` into a `FixtureScope` expression.
+fn parse_fixture_scope(
+    attr: proc_macro2::TokenStream,
+) -> Result<proc_macro2::TokenStream, syn::Error> {
+    if attr.is_empty() {
+        return Ok(quote! { ::satchel::FixtureScope::Test });
+    }
+    let scope = syn::parse2::<MetaNameValue>(attr.clone())
+        .ok()
+        .filter(|meta| meta.path.is_ident("scope"))
+        .and_then(|meta| expect_str_lit(meta.value).ok())
+        .ok_or_else(|| syn::Error::new_spanned(&attr, FIXTURE_UNSUPPORTED))?;
+    match scope.value().as_str() {
+        "test" => Ok(quote! { ::satchel::FixtureScope::Test }),
+        "module" => Ok(quote! { ::satchel::FixtureScope::Module }),
+        "run" => Ok(quote! { ::satchel::FixtureScope::Run }),
+        _ => Err(syn::Error::new_spanned(scope, FIXTURE_UNSUPPORTED)),
+    }
+}
+
+/// Returns `T` for a return type spelled `Result<T, ...>`.
+fn result_ok_type(ty: &syn::Type) -> Option<&syn::Type> {
+    let syn::Type::Path(path) = ty else {
+        return None;
+    };
+    let segment = path.path.segments.last()?;
+    if segment.ident != "Result" {
+        return None;
+    }
+    let syn::PathArguments::AngleBracketed(args) = &segment.arguments else {
+        return None;
+    };
+    match args.args.first()? {
+        syn::GenericArgument::Type(ty) => Some(ty),
+        _ => None,
+    }
+}
+
+/// Name of the `FixtureHandle` constant generated next to the fixture function `name`.
+fn fixture_handle(name: &syn::Ident) -> syn::Ident {
+    format_ident!(
+        "__SATCHEL_FIXTURE_{}_HANDLE",
+        name.to_string().to_uppercase(),
+        span = name.span()
     )
 }
 
+fn expand_fixture(
+    attr: proc_macro2::TokenStream,
+    input: TokenStream,
+) -> Result<proc_macro2::TokenStream, syn::Error> {
+    let input_fn: ItemFn = syn::parse(input)?;
+    let scope = parse_fixture_scope(attr)?;
+
+    let sig = &input_fn.sig;
+    let output = match &sig.output {
+        syn::ReturnType::Type(_, ty)
+            if sig.asyncness.is_none()
+                && sig.inputs.is_empty()
+                && sig.generics.params.is_empty() =>
+        {
+            ty
+        }
+        _ => return Err(syn::Error::new_spanned(sig, FIXTURE_SIGNATURE)),
+    };
+
+    let fn_name = &sig.ident;
+    let fn_name_str = fn_name.to_string();
+    let (value_type, setup) = match result_ok_type(output) {
+        Some(value_type) => (value_type, quote! { ::satchel::fixture_result(#fn_name()) }),
+        None => (&**output, quote! { ::satchel::fixture_value(#fn_name()) }),
+    };
+    let static_name = format_ident!("__SATCHEL_FIXTURE_{}", fn_name_str.to_uppercase());
+    let handle_name = fixture_handle(fn_name);
+    let vis = &input_fn.vis;
+    let location = quote_spanned! {fn_name.span()=>
+        ::satchel::SourceLocation {
+            file: ::core::file!(),
+            line: ::core::line!(),
+            column: ::core::column!(),
+        }
+    };
+
+    // Tests name the fixture as a parameter and find its typed handle in a constant next to the
+    // function, which a glob import brings along into other modules.
+    Ok(quote! {
+        #input_fn
+
+        #[doc(hidden)]
+        #[linkme::distributed_slice(::satchel::test_harness::FIXTURES)]
+        static #static_name: ::satchel::Fixture = ::satchel::Fixture {
+            name: #fn_name_str,
+            module_path: ::core::module_path!(),
+            location: #location,
+            scope: #scope,
+            setup: || #setup,
+        };
+
+        #[doc(hidden)]
+        #vis const #handle_name: ::satchel::FixtureHandle<#value_type> =
+            ::satchel::FixtureHandle::new(&#static_name);
+    })
+}
+
 fn expand_test_or_bench(
     attr: TokenStream,
     input: TokenStream,
     kind: proc_macro2::TokenStream,
     prefix: &str,
+    allows_bencher: bool,
 ) -> TokenStream {
     let mut input_fn = parse_macro_input!(input as ItemFn);
 
@@ -314,33 +1187,177 @@ fn expand_test_or_bench(
         Err(e) => return e.into_compile_error().into(),
     };
 
+    let entry_point = match entry_point(&input_fn.sig, &input_fn.attrs, allows_bencher) {
+        Ok(ts) => ts,
+        Err(e) => return e.into_compile_error().into(),
+    };
+
+    let mut case_args = match parse_case_attributes(attr.into()) {
+        Ok(args) => args,
+        Err(e) => return e.into_compile_error().into(),
+    };
+    match parse_tag_attrs(&input_fn.attrs) {
+        Ok(tags) => case_args.tags.extend(tags),
+        Err(e) => return e.into_compile_error().into(),
+    }
+
     let fn_name = &input_fn.sig.ident;
     let fn_name_str = fn_name.to_string();
-    let static_name = format_ident!("{}{}", prefix, fn_name_str.to_uppercase());
-    let case_attribute_literals = match parse_case_attributes(attr) {
-        Ok(list) => list,
+    let registration = Registration {
+        static_name: format_ident!("{}{}", prefix, fn_name_str.to_uppercase()),
+        vis: input_fn.vis.clone(),
+        name: fn_name_str,
+        // Resolve line!()/column!() at the function name so the location points at the test itself.
+        location_span: fn_name.span(),
+        kind,
+        entry_point,
+        is_async: input_fn.sig.asyncness.is_some(),
+        should_panic,
+        ignore,
+        // entry_point() has rejected a `&mut Bencher` next to other parameters.
+        fixtures: match bencher_param(&input_fn.sig) {
+            Some(_) => Vec::new(),
+            None => match fixture_params(&input_fn.sig) {
+                Ok(fixtures) => fixtures,
+                Err(e) => return e.into_compile_error().into(),
+            },
+        },
+        case_args,
+    };
+    let registration = match registration.into_tokens() {
+        Ok(ts) => ts,
         Err(e) => return e.into_compile_error().into(),
     };
 
-    // Remove should_panic and ignore attributes from the function since we've processed them
-    input_fn
-        .attrs
-        .retain(|attr| !attr.path().is_ident("should_panic") && !attr.path().is_ident("ignore"));
+    // Remove should_panic, ignore and tag attributes from the function since we've processed them
+    input_fn.attrs.retain(|attr| {
+        !attr.path().is_ident("should_panic")
+            && !attr.path().is_ident("ignore")
+            && !is_tag_attr(attr)
+    });
 
     let expanded = quote! {
-        #[linkme::distributed_slice(::satchel::test_harness::TESTS)]
-        static #static_name: ::satchel::TestCase = ::satchel::TestCase {
-            name: #fn_name_str,
-            module_path: ::core::module_path!(),
-            kind: #kind,
-            test_fn: #fn_name,
-            should_panic: #should_panic,
-            ignore: #ignore,
-            case_attributes: &[ #( #case_attribute_literals ),* ] as &'static [&'static str],
-        };
+        #registration
 
         #input_fn
     };
 
     TokenStream::from(expanded)
 }
+
+fn expand_test_case(
+    attr: proc_macro2::TokenStream,
+    input: TokenStream,
+) -> Result<proc_macro2::TokenStream, syn::Error> {
+    let mut input_fn: ItemFn = syn::parse(input)?;
+
+    // The outermost #[test_case] invokes the macro; the rows below it are still attached to the
+    // function and are expanded here in source order.
+    let mut rows = vec![TestCaseRow::parse(attr, proc_macro2::Span::call_site())?];
+    for attr in input_fn.attrs.iter().filter(|attr| is_test_case_attr(attr)) {
+        let syn::Meta::List(list) = &attr.meta else {
+            return Err(syn::Error::new_spanned(attr, TEST_CASE_EXPECTED_LIST));
+        };
+        rows.push(TestCaseRow::parse(list.tokens.clone(), attr.span())?);
+    }
+    input_fn.attrs.retain(|attr| !is_test_case_attr(attr));
+
+    let should_panic = parse_should_panic_attr(&input_fn.attrs)?;
+    let ignore = parse_ignore_attr(&input_fn.attrs)?;
+    let tags = parse_tag_attrs(&input_fn.attrs)?;
+
+    let fn_name = &input_fn.sig.ident;
+    let fn_name_str = fn_name.to_string();
+    let arity = input_fn.sig.inputs.len();
+    let is_async = input_fn.sig.asyncness.is_some();
+    let output = &input_fn.sig.output;
+
+    let mut case_names = Vec::with_capacity(rows.len());
+    let mut expanded = proc_macro2::TokenStream::new();
+    for (index, row) in rows.into_iter().enumerate() {
+        let index = index + 1;
+        if row.args.len() != arity {
+            return Err(syn::Error::new(
+                row.span,
+                format!(
+                    "#[test_case] expects {} argument(s) for `{}`, found {}",
+                    arity,
+                    fn_name_str,
+                    row.args.len()
+                ),
+            ));
+        }
+
+        let case_name = row.case_name(index)?;
+        if case_names.contains(&case_name) {
+            return Err(match &row.label {
+                Some(label) => syn::Error::new_spanned(label, DUP_TEST_CASE_LABEL),
+                None => syn::Error::new(row.span, DUP_TEST_CASE_LABEL),
+            });
+        }
+
+        let wrapper_name = format_ident!("__satchel_case_{}_{}", fn_name, index);
+        let args = &row.args;
+        let call = if is_async {
+            quote! { #fn_name(#( #args ),*).await }
+        } else {
+            quote! { #fn_name(#( #args ),*) }
+        };
+        let asyncness = is_async.then(|| quote! { async });
+        let wrapper: ItemFn = match &row.expected {
+            Some(expected) => syn::parse_quote! {
+                #[doc(hidden)]
+                #asyncness fn #wrapper_name() {
+                    ::core::assert_eq!(#call, #expected);
+                }
+            },
+            None => syn::parse_quote! {
+                #[doc(hidden)]
+                #asyncness fn #wrapper_name() #output {
+                    #call
+                }
+            },
+        };
+
+        let registration = Registration {
+            name: format!("{}::{}", fn_name_str, case_name),
+            static_name: format_ident!(
+                "__SATCHEL_TEST_{}_CASE_{}",
+                fn_name_str.to_uppercase(),
+                index
+            ),
+            vis: syn::Visibility::Inherited,
+            location_span: row.span,
+            kind: quote! { ::satchel::TestKind::Unit },
+            entry_point: entry_point(&wrapper.sig, &input_fn.attrs, false)?,
+            is_async,
+            should_panic: should_panic.clone(),
+            ignore: ignore.clone(),
+            fixtures: Vec::new(),
+            case_args: CaseArgs {
+                tags: [row.case_args.tags, tags.clone()].concat(),
+                ..row.case_args
+            },
+        }
+        .into_tokens()?;
+
+        expanded.extend(quote! {
+            #registration
+            #wrapper
+        });
+        case_names.push(case_name);
+    }
+
+    // Remove should_panic, ignore and tag attributes from the function since we've processed them
+    input_fn.attrs.retain(|attr| {
+        !attr.path().is_ident("should_panic")
+            && !attr.path().is_ident("ignore")
+            && !is_tag_attr(attr)
+    });
+
+    Ok(quote! {
+        #expanded
+
+        #input_fn
+    })
+}
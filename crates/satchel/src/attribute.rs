@@ -0,0 +1,89 @@
+//! Case attributes: markers and `key = value` settings attached through `#[test(...)]`.
+
+/// Value of a [`CaseAttribute`], typed after the literal written in the attribute.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum AttributeValue {
+    /// A marker without a value: a bare identifier such as `flaky`, or a string literal such as
+    /// `"--threads=4"` kept verbatim as the key.
+    Flag,
+    /// `key = "text"`
+    Str(&'static str),
+    /// `key = 4`
+    Int(i64),
+    /// `key = true`
+    Bool(bool),
+    /// `key = 0.5`
+    Float(f64),
+}
+
+impl AttributeValue {
+    /// The text of a [`AttributeValue::Str`].
+    pub fn as_str(&self) -> Option<&'static str> {
+        match *self {
+            AttributeValue::Str(text) => Some(text),
+            _ => None,
+        }
+    }
+
+    /// The number of an [`AttributeValue::Int`].
+    pub fn as_int(&self) -> Option<i64> {
+        match *self {
+            AttributeValue::Int(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    /// The value of an [`AttributeValue::Bool`].
+    pub fn as_bool(&self) -> Option<bool> {
+        match *self {
+            AttributeValue::Bool(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    /// The number of an [`AttributeValue::Float`], or of an [`AttributeValue::Int`] converted to
+    /// `f64`, so `ratio = 1` and `ratio = 1.0` read the same.
+    pub fn as_float(&self) -> Option<f64> {
+        match *self {
+            AttributeValue::Float(value) => Some(value),
+            AttributeValue::Int(value) => Some(value as f64),
+            _ => None,
+        }
+    }
+}
+
+/// One entry of [`crate::TestCase::case_attributes`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CaseAttribute {
+    /// Name of the setting, or the whole marker for a [`AttributeValue::Flag`].
+    pub key: &'static str,
+    /// Value written after `=`, or [`AttributeValue::Flag`].
+    pub value: AttributeValue,
+}
+
+impl CaseAttribute {
+    /// Looks up the value of the first attribute called `key` in `attributes`.
+    pub fn find(
+        attributes: &'static [CaseAttribute],
+        key: &str,
+    ) -> Option<&'static AttributeValue> {
+        attributes
+            .iter()
+            .find(|attribute| attribute.key == key)
+            .map(|attribute| &attribute.value)
+    }
+}
+
+/// Renders flags as their key and settings as `key=value`, the form used in listings and
+/// reports.
+impl core::fmt::Display for CaseAttribute {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self.value {
+            AttributeValue::Flag => f.write_str(self.key),
+            AttributeValue::Str(text) => write!(f, "{}={}", self.key, text),
+            AttributeValue::Int(value) => write!(f, "{}={}", self.key, value),
+            AttributeValue::Bool(value) => write!(f, "{}={}", self.key, value),
+            AttributeValue::Float(value) => write!(f, "{}={:?}", self.key, value),
+        }
+    }
+}
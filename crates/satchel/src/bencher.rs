@@ -0,0 +1,185 @@
+//! Benchmarks taking a [`Bencher`], which separate setup from the measured routine.
+
+use alloc::vec::Vec;
+use core::hint::black_box;
+use core::time::Duration;
+
+/// Inputs of [`Bencher::iter_batched`] set up at once, which bounds the memory they take.
+const MAX_BATCH: u64 = 1024;
+
+/// Amount of data one iteration of a benchmark processes, declared with
+/// [`Bencher::set_throughput`] so the harness can report a rate next to the time.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Throughput {
+    /// Bytes per iteration, reported in MB/s.
+    Bytes(u64),
+    /// Elements per iteration, reported in elements/s.
+    Elements(u64),
+}
+
+/// Clock handed to each sample by the [`BenchDriver`].
+pub trait Timer {
+    /// Starts timing, discarding an earlier start.
+    fn start(&mut self);
+    /// Time since the last [`Timer::start`].
+    fn elapsed(&self) -> Duration;
+}
+
+/// Measurement loop supplied by the harness to a [`Bencher`].
+///
+/// Satchel has no clock of its own; the harness decides how many samples to take and how many
+/// iterations each one runs, and provides the [`Timer`] that the samples use.
+pub trait BenchDriver {
+    /// Measures a routine by calling `sample(iterations, timer)` as often as it needs. Each call
+    /// runs the routine `iterations` times and returns the time spent in the routine itself,
+    /// excluding setup, as measured with `timer`.
+    fn measure(&mut self, sample: &mut dyn FnMut(u64, &mut dyn Timer) -> Duration);
+}
+
+/// Argument of a `#[bench]` function written as `fn(&mut Bencher)`.
+///
+/// The function does its setup, then hands the code to measure to [`Bencher::iter`], or to
+/// [`Bencher::iter_batched`] when every iteration needs a fresh input. Outside of a benchmark
+/// run, harnesses run each routine once.
+pub struct Bencher<'a> {
+    driver: &'a mut dyn BenchDriver,
+    throughput: Option<Throughput>,
+}
+
+impl<'a> Bencher<'a> {
+    /// Creates a bencher measuring with `driver`.
+    pub fn new(driver: &'a mut dyn BenchDriver) -> Self {
+        Bencher {
+            driver,
+            throughput: None,
+        }
+    }
+
+    /// Declares how much data one iteration processes.
+    pub fn set_throughput(&mut self, throughput: Throughput) {
+        self.throughput = Some(throughput);
+    }
+
+    /// Throughput declared by the benchmark, if any.
+    pub fn throughput(&self) -> Option<Throughput> {
+        self.throughput
+    }
+
+    /// Measures `routine`. Its result is passed through [`black_box`] and dropped within the
+    /// measured time.
+    pub fn iter<O>(&mut self, mut routine: impl FnMut() -> O) {
+        self.driver.measure(&mut |iterations, timer| {
+            timer.start();
+            for _ in 0..iterations {
+                black_box(routine());
+            }
+            timer.elapsed()
+        });
+    }
+
+    /// Measures `routine` on inputs created by `setup`, one per iteration. Only the routine is
+    /// timed; setting up the inputs and dropping the results is not.
+    pub fn iter_batched<I, O>(
+        &mut self,
+        mut setup: impl FnMut() -> I,
+        mut routine: impl FnMut(I) -> O,
+    ) {
+        self.driver.measure(&mut |iterations, timer| {
+            let mut elapsed = Duration::ZERO;
+            let mut remaining = iterations;
+            while remaining > 0 {
+                let batch = remaining.min(MAX_BATCH);
+                remaining -= batch;
+                let inputs: Vec<I> = (0..batch).map(|_| setup()).collect();
+                let mut outputs = Vec::with_capacity(inputs.len());
+                timer.start();
+                for input in inputs {
+                    outputs.push(routine(black_box(input)));
+                }
+                elapsed += timer.elapsed();
+                black_box(outputs);
+            }
+            elapsed
+        });
+    }
+}
+
+/// Driver running each sample once with a single iteration, for benchmarks run as tests.
+pub(crate) struct RunOnce;
+
+struct NoClock;
+
+impl Timer for NoClock {
+    fn start(&mut self) {}
+
+    fn elapsed(&self) -> Duration {
+        Duration::ZERO
+    }
+}
+
+impl BenchDriver for RunOnce {
+    fn measure(&mut self, sample: &mut dyn FnMut(u64, &mut dyn Timer) -> Duration) {
+        sample(1, &mut NoClock);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{BenchDriver, Bencher, Throughput, Timer};
+    use alloc::vec::Vec;
+    use core::cell::Cell;
+    use core::time::Duration;
+
+    /// Timer advancing by one nanosecond per start, so samples report how often they timed.
+    struct CountingTimer(u64);
+
+    impl Timer for CountingTimer {
+        fn start(&mut self) {
+            self.0 += 1;
+        }
+
+        fn elapsed(&self) -> Duration {
+            Duration::from_nanos(1)
+        }
+    }
+
+    struct Samples(&'static [u64], Vec<Duration>);
+
+    impl BenchDriver for Samples {
+        fn measure(&mut self, sample: &mut dyn FnMut(u64, &mut dyn Timer) -> Duration) {
+            for &iterations in self.0 {
+                let mut timer = CountingTimer(0);
+                let elapsed = sample(iterations, &mut timer);
+                assert_eq!(elapsed, Duration::from_nanos(timer.0));
+                self.1.push(elapsed);
+            }
+        }
+    }
+
+    #[test]
+    fn batched_inputs_are_set_up_per_iteration_in_bounded_batches() {
+        let mut driver = Samples(&[1, 1024, 2500], Vec::new());
+        let (setups, runs) = (Cell::new(0u64), Cell::new(0u64));
+        let mut bencher = Bencher::new(&mut driver);
+        bencher.set_throughput(Throughput::Bytes(64));
+        bencher.iter_batched(
+            || setups.set(setups.get() + 1),
+            |()| runs.set(runs.get() + 1),
+        );
+        assert_eq!(bencher.throughput(), Some(Throughput::Bytes(64)));
+
+        assert_eq!((setups.get(), runs.get()), (3525, 3525));
+        // One timed batch per 1024 inputs.
+        let timed: Vec<u64> = driver.1.iter().map(|d| d.as_nanos() as u64).collect();
+        assert_eq!(timed, [1, 1, 3]);
+    }
+
+    #[test]
+    fn iter_times_all_iterations_of_a_sample_together() {
+        let mut driver = Samples(&[1000], Vec::new());
+        let runs = Cell::new(0u64);
+        Bencher::new(&mut driver).iter(|| runs.set(runs.get() + 1));
+        assert_eq!(runs.get(), 1000);
+        assert_eq!(driver.1, [Duration::from_nanos(1)]);
+    }
+}
@@ -0,0 +1,144 @@
+//! Fixtures: values set up by the harness and handed to tests through their parameters.
+
+use crate::{SourceLocation, TestError};
+use alloc::format;
+use alloc::sync::Arc;
+use alloc::vec::Vec;
+use core::any::{Any, type_name};
+use core::marker::PhantomData;
+
+/// How long the value of a fixture lives, and so which tests share it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum FixtureScope {
+    /// Set up for each test that requests it and torn down when that test finishes.
+    Test,
+    /// Shared by the tests of one module; torn down after the last of them finishes.
+    Module,
+    /// Shared by all tests of a run; torn down when the run finishes.
+    Run,
+}
+
+/// A fixture value, type-erased so the harness can store values of any fixture.
+pub type FixtureValue = Arc<dyn Any + Send + Sync>;
+
+/// Generated wrapper around a fixture function.
+pub type FixtureSetupFn = fn() -> Result<FixtureValue, TestError>;
+
+/// Static description of a function registered with [`macro@crate::fixture`].
+#[derive(Debug)]
+pub struct Fixture {
+    /// Name of the fixture function, which is also the parameter name that requests it.
+    pub name: &'static str,
+    /// Fully qualified module path of the fixture function.
+    pub module_path: &'static str,
+    /// Source position of the fixture function.
+    pub location: SourceLocation,
+    /// Lifetime of the value, from `#[fixture(scope = "...")]`.
+    pub scope: FixtureScope,
+    /// Creates the value. Teardown is dropping the last reference to it.
+    pub setup: FixtureSetupFn,
+}
+
+/// Typed reference to a [`Fixture`], generated next to the fixture function so that tests
+/// requesting it are type-checked against the value it provides.
+pub struct FixtureHandle<T> {
+    fixture: &'static Fixture,
+    value: PhantomData<fn() -> T>,
+}
+
+impl<T> FixtureHandle<T> {
+    #[doc(hidden)]
+    pub const fn new(fixture: &'static Fixture) -> Self {
+        FixtureHandle {
+            fixture,
+            value: PhantomData,
+        }
+    }
+
+    /// The fixture this handle refers to.
+    pub const fn fixture(self) -> &'static Fixture {
+        self.fixture
+    }
+}
+
+impl<T> Clone for FixtureHandle<T> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T> Copy for FixtureHandle<T> {}
+
+/// Values of the fixtures a case requested, in the order of [`crate::TestCase::fixtures`].
+///
+/// Built by the harness before it invokes an [`crate::EntryPoint::Fixtures`] or
+/// [`crate::EntryPoint::AsyncFixtures`] entry point.
+#[derive(Clone, Default)]
+pub struct FixtureValues {
+    values: Vec<FixtureValue>,
+}
+
+impl core::fmt::Debug for FixtureValues {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("FixtureValues")
+            .field("len", &self.values.len())
+            .finish()
+    }
+}
+
+impl FixtureValues {
+    pub fn new(values: Vec<FixtureValue>) -> Self {
+        FixtureValues { values }
+    }
+
+    /// Returns the values, e.g. to tear them down in a specific order.
+    pub fn into_inner(self) -> Vec<FixtureValue> {
+        self.values
+    }
+
+    fn value<T: Any>(&self, index: usize, handle: FixtureHandle<T>) -> &FixtureValue {
+        let value = self.values.get(index).unwrap_or_else(|| {
+            panic!(
+                "fixture `{}` was not set up by the harness",
+                handle.fixture.name
+            )
+        });
+        if !(**value).is::<T>() {
+            panic!(
+                "fixture `{}` does not provide a value of type `{}`",
+                handle.fixture.name,
+                type_name::<T>()
+            );
+        }
+        value
+    }
+
+    #[doc(hidden)]
+    pub fn get<T: Any>(&self, index: usize, handle: FixtureHandle<T>) -> &T {
+        (**self.value(index, handle)).downcast_ref().unwrap()
+    }
+
+    #[doc(hidden)]
+    pub fn shared<T: Any + Send + Sync>(&self, index: usize, handle: FixtureHandle<T>) -> Arc<T> {
+        Arc::clone(self.value(index, handle)).downcast().unwrap()
+    }
+}
+
+#[doc(hidden)]
+pub fn fixture_value<T: Any + Send + Sync>(value: T) -> Result<FixtureValue, TestError> {
+    Ok(Arc::new(value))
+}
+
+#[doc(hidden)]
+pub fn fixture_result<T, E>(result: Result<T, E>) -> Result<FixtureValue, TestError>
+where
+    T: Any + Send + Sync,
+    E: core::fmt::Debug,
+{
+    match result {
+        Ok(value) => fixture_value(value),
+        Err(err) => Err(TestError {
+            debug: format!("{:?}", err),
+        }),
+    }
+}
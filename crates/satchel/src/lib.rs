@@ -3,17 +3,51 @@
 //! This crate collects metadata for functions annotated with [`macro@test`] and
 //! [`macro@bench`], storing them in a linkme-powered distributed slice. Harnesses invoke
 //! [`get_tests!`] to enumerate the [`TestCase`] entries for the current crate, inspect
-//! [`TestCase::case_attributes`] for custom markers, and run or filter tests and benchmarks as
+//! [`TestCase::case_attributes`] for custom markers and settings, and run or filter tests and benchmarks as
 //! needed. See the project README for end-to-end examples, including CTest integration and
 //! `libtest-mimic` runners.
 #![no_std]
 
+extern crate alloc;
+#[cfg(feature = "std")]
+extern crate std;
+
+mod attribute;
+mod bencher;
+mod fixture;
+
+pub use attribute::{AttributeValue, CaseAttribute};
+pub use bencher::{BenchDriver, Bencher, Throughput, Timer};
+pub use fixture::{
+    Fixture, FixtureHandle, FixtureScope, FixtureSetupFn, FixtureValue, FixtureValues,
+};
+#[doc(hidden)]
+pub use fixture::{fixture_result, fixture_value};
+
+use alloc::boxed::Box;
+use alloc::format;
+use alloc::string::String;
+use core::future::Future;
+use core::pin::Pin;
+use core::task::{Context, Poll, Waker};
+
 /// The main macro of the satchel crate used to register unit tests with the Satchel harness.
-/// It works like the standard Rust [test macro](https://doc.rust-lang.org/rust-by-example/testing/unit_testing.html#unit-testing), 
+/// It works like the standard Rust [test macro](https://doc.rust-lang.org/rust-by-example/testing/unit_testing.html#unit-testing),
 /// but can be used with custom test harnesses.
 ///
-/// Satchel supports the standard `#[ignore]` and `#[should_panic="..."]` attributes, 
-/// as well as custom arguments that can be freely interpreted by the test harness via `#[test(...)]`
+/// Satchel supports the standard `#[ignore]` and `#[should_panic="..."]` attributes, as well as
+/// conditional `#[ignore(if_env = "CI")]`, `#[ignore(unless_env = "VAR")]` and
+/// `#[ignore(if = predicate, reason = "...")]` evaluated by the harness (see [`IgnoreCondition`]).
+/// A test can also skip itself while running with [`skip!`]. Custom arguments that can be freely
+/// interpreted by the test harness are passed via `#[test(...)]`:
+/// bare identifiers and string literals become flags, and `key = value` with a string, integer,
+/// float or bool literal becomes a typed [`CaseAttribute`], e.g. `#[test(threads = 4, flaky)]`.
+///
+/// Like with the standard test macro, the function may return `Result<(), E>` for any
+/// `E: Debug`, so tests can use the `?` operator. An `Err` is reported as a [`TestError`].
+///
+/// `async fn` tests are registered as [`EntryPoint::Async`] and driven by an [`Executor`]
+/// supplied by the harness. `#[test(executor = "name")]` asks the harness for a specific one.
 ///
 /// # Examples
 /// ```no_run
@@ -29,9 +63,13 @@ pub use satchel_macro::test;
 /// This is a variant of the [`macro@test`] macro that will result in [TestKind::Benchmark].
 /// See the documentation on [`macro@test`] for details.
 ///
+/// A benchmark is either measured as a whole, or takes a `&mut` [`Bencher`] to keep its setup
+/// out of the measurement and declare a [`Throughput`]. It is then registered as
+/// [`EntryPoint::Bench`], and may not be `async` or request fixtures.
+///
 /// # Examples
 /// ```no_run
-/// use satchel::bench;
+/// use satchel::{Bencher, Throughput, bench};
 ///
 /// #[bench]
 /// fn spin_loop() {
@@ -41,12 +79,266 @@ pub use satchel_macro::test;
 ///     }
 ///     assert!(sum > 0);
 /// }
+///
+/// #[bench]
+/// fn sorts(b: &mut Bencher) {
+///     let data: Vec<u32> = (0..1000).rev().collect();
+///     b.set_throughput(Throughput::Elements(1000));
+///     b.iter_batched(|| data.clone(), |mut data| data.sort());
+/// }
 /// ```
 pub use satchel_macro::bench;
 
+/// Hides a value from the optimizer, so a benchmark body computing it is not removed as dead
+/// code and its inputs are not constant-folded.
+///
+/// # Examples
+/// ```no_run
+/// use satchel::{bench, black_box};
+///
+/// #[bench]
+/// fn sums_up() {
+///     let sum: u64 = (0..black_box(1000u64)).sum();
+///     black_box(sum);
+/// }
+/// ```
+pub use core::hint::black_box;
+
+/// Registers a function as a fixture that tests request by naming it as a parameter.
+///
+/// The fixture function takes no arguments and returns the value, or `Result<T, E>` for any
+/// `E: Debug` if setup can fail. Tests receive a shared reference to it; the value is torn down
+/// by dropping it, which the harness does even if the test panics. Values must be
+/// `Send + Sync + 'static`.
+///
+/// `#[fixture(scope = "...")]` selects the [`FixtureScope`]: `"test"` (the default), `"module"`
+/// or `"run"`. Fixtures must be declared at module level, not inside a function body; tests in
+/// other modules reach them through a glob import such as `use super::*`.
+///
+/// # Examples
+/// ```no_run
+/// use satchel::{fixture, test};
+///
+/// struct Database;
+///
+/// #[fixture(scope = "module")]
+/// fn database() -> Database {
+///     Database
+/// }
+///
+/// #[test]
+/// fn queries(database: &Database) {
+///     let _ = database;
+/// }
+/// # fn main() {}
+/// ```
+pub use satchel_macro::fixture;
+
+/// Registers one unit test per `#[test_case(...)]` row stacked on a function with arguments.
+///
+/// Each row lists the arguments, optionally followed by `=> expected` to compare the return
+/// value with `assert_eq!`. After a `;`, a row may carry a `label = "..."` and the same case
+/// attributes as [`macro@test`]. Cases are named `function::case_<n>`, or after the sanitized
+/// label when one is given. `#[should_panic]`, `#[ignore]` and `#[tag]` apply to every row.
+///
+/// # Examples
+/// ```no_run
+/// use satchel::test_case;
+///
+/// #[test_case(1, 2 => 3)]
+/// #[test_case(-1, 1 => 0; label = "cancels out")]
+/// #[test_case(40, 2 => 42; threads = 4, retry_on_failure, tags(slow))]
+/// fn add(left: i32, right: i32) -> i32 {
+///     left + right
+/// }
+/// ```
+pub use satchel_macro::test_case;
+
+/// Adds tags to the test, benchmark or `#[test_case]` rows it is placed below, like
+/// `tags(...)` inside [`macro@test`]. Tags are identifiers or string literals made of
+/// letters, digits, `_`, `-`, `.` and `:`, and end up in [`TestCase::tags`].
+///
+/// Like `#[ignore]`, the attribute is consumed by the macro above it and needs no import.
+/// Anywhere else, `#[satchel::tag(...)]` fails to compile with a hint about the placement.
+///
+/// # Examples
+/// ```no_run
+/// use satchel::test;
+///
+/// #[test]
+/// #[tag(smoke, "needs-gpu-stub")]
+/// fn renders_frame() {}
+/// ```
+pub use satchel_macro::tag;
+
 /// Function pointer for bare test entry points.
 pub type TestFn = fn();
 
+/// Function pointer for wrapped entry points of tests returning `Result<(), E>`.
+pub type ResultTestFn = fn() -> Result<(), TestError>;
+
+/// Failure returned by a test written as `fn() -> Result<(), E>`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TestError {
+    /// `Debug` rendering of the error value the test returned.
+    pub debug: String,
+}
+
+impl core::fmt::Display for TestError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.write_str(&self.debug)
+    }
+}
+
+/// Return types accepted from test functions.
+pub trait TestReturn {
+    /// Converts the returned value into the harness-facing result.
+    fn into_test_result(self) -> Result<(), TestError>;
+}
+
+impl TestReturn for () {
+    fn into_test_result(self) -> Result<(), TestError> {
+        Ok(())
+    }
+}
+
+impl<E: core::fmt::Debug> TestReturn for Result<(), E> {
+    fn into_test_result(self) -> Result<(), TestError> {
+        self.map_err(|err| TestError {
+            debug: format!("{:?}", err),
+        })
+    }
+}
+
+/// Future produced by an `async fn` test.
+pub type TestFuture = Pin<Box<dyn Future<Output = Result<(), TestError>>>>;
+
+/// Function pointer creating the future of an `async fn` test.
+pub type AsyncTestFn = fn() -> TestFuture;
+
+/// Generated wrapper around a benchmark written as `fn(&mut Bencher)`.
+pub type BenchFn = fn(&mut Bencher<'_>) -> Result<(), TestError>;
+
+/// Generated wrapper around a test that requests fixtures through its parameters.
+pub type FixtureTestFn = fn(&FixtureValues) -> Result<(), TestError>;
+
+/// Generated wrapper around an `async fn` test that requests fixtures.
+pub type AsyncFixtureTestFn = fn(&FixtureValues) -> TestFuture;
+
+#[doc(hidden)]
+pub fn into_test_future<F>(future: F) -> TestFuture
+where
+    F: Future + 'static,
+    F::Output: TestReturn,
+{
+    Box::pin(async move { future.await.into_test_result() })
+}
+
+/// Drives the future of an `async fn` test to completion.
+///
+/// Satchel does not depend on an async runtime; harnesses implement this trait for the runtime
+/// their tests need and pick one per case, see [`TestCase::executor`].
+pub trait Executor {
+    /// Runs `future` to completion on the calling thread and returns its result.
+    fn block_on(&self, future: TestFuture) -> Result<(), TestError>;
+}
+
+/// Minimal executor that polls the future on the calling thread until it completes.
+///
+/// Between polls it parks the thread until the future wakes it, so it only suits futures that
+/// do not rely on a runtime's reactor or timers. Without the `std` feature it spins instead.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct DefaultExecutor;
+
+/// Waker of [`DefaultExecutor`], unparking the thread that polls the future.
+#[cfg(feature = "std")]
+struct ThreadWaker(std::thread::Thread);
+
+#[cfg(feature = "std")]
+impl alloc::task::Wake for ThreadWaker {
+    fn wake(self: alloc::sync::Arc<Self>) {
+        self.0.unpark();
+    }
+
+    fn wake_by_ref(self: &alloc::sync::Arc<Self>) {
+        self.0.unpark();
+    }
+}
+
+impl Executor for DefaultExecutor {
+    fn block_on(&self, mut future: TestFuture) -> Result<(), TestError> {
+        #[cfg(feature = "std")]
+        let waker = Waker::from(alloc::sync::Arc::new(ThreadWaker(std::thread::current())));
+        #[cfg(not(feature = "std"))]
+        let waker = Waker::noop().clone();
+        let mut cx = Context::from_waker(&waker);
+        loop {
+            if let Poll::Ready(result) = future.as_mut().poll(&mut cx) {
+                return result;
+            }
+            #[cfg(feature = "std")]
+            std::thread::park();
+            #[cfg(not(feature = "std"))]
+            core::hint::spin_loop();
+        }
+    }
+}
+
+/// Entry point registered for a case.
+#[derive(Debug, Clone, Copy)]
+pub enum EntryPoint {
+    /// A bare `fn()` that signals failure by panicking.
+    Plain(TestFn),
+    /// A generated wrapper around a function returning `Result<(), E>`.
+    Result(ResultTestFn),
+    /// A generated wrapper around an `async fn`, run by an [`Executor`].
+    Async(AsyncTestFn),
+    /// A generated wrapper around a test with fixture parameters, see [`TestCase::fixtures`].
+    Fixtures(FixtureTestFn),
+    /// Like [`EntryPoint::Fixtures`], for an `async fn`.
+    AsyncFixtures(AsyncFixtureTestFn),
+    /// A generated wrapper around a benchmark taking a [`Bencher`]. Harnesses measuring it
+    /// call the function with a bencher of their own [`BenchDriver`].
+    Bench(BenchFn),
+}
+
+impl EntryPoint {
+    /// Invokes the entry point, returning the error of `Result`-returning tests.
+    ///
+    /// Async entry points are driven by [`DefaultExecutor`]. Panics raised by the test are not
+    /// caught.
+    pub fn call(&self) -> Result<(), TestError> {
+        self.run(&DefaultExecutor)
+    }
+
+    /// Like [`EntryPoint::call`], but drives async entry points with the given executor.
+    pub fn run(&self, executor: &dyn Executor) -> Result<(), TestError> {
+        self.run_with_fixtures(executor, &FixtureValues::default())
+    }
+
+    /// Like [`EntryPoint::run`], passing the values of the fixtures the case requested.
+    ///
+    /// Entry points with fixture parameters panic if a value is missing. [`EntryPoint::Bench`]
+    /// runs the routines of the benchmark once, without measuring them.
+    pub fn run_with_fixtures(
+        &self,
+        executor: &dyn Executor,
+        fixtures: &FixtureValues,
+    ) -> Result<(), TestError> {
+        match self {
+            EntryPoint::Plain(test_fn) => {
+                test_fn();
+                Ok(())
+            }
+            EntryPoint::Result(test_fn) => test_fn(),
+            EntryPoint::Async(test_fn) => executor.block_on(test_fn()),
+            EntryPoint::Fixtures(test_fn) => test_fn(fixtures),
+            EntryPoint::AsyncFixtures(test_fn) => executor.block_on(test_fn(fixtures)),
+            EntryPoint::Bench(test_fn) => test_fn(&mut Bencher::new(&mut bencher::RunOnce)),
+        }
+    }
+}
+
 /// Classification of a registered case.
 #[derive(Debug, Clone, Copy, PartialEq)]
 pub enum TestKind {
@@ -68,6 +360,81 @@ pub struct ShouldPanic {
 pub struct Ignore {
     /// Optional reason string carried alongside the skip flag.
     pub reason: Option<&'static str>,
+    /// Condition from `#[ignore(...)]` that the harness evaluates when the run starts. `None`
+    /// skips the case unconditionally.
+    pub condition: Option<IgnoreCondition>,
+}
+
+/// Condition under which an `#[ignore(...)]` case is skipped.
+#[derive(Debug, Clone, Copy)]
+pub enum IgnoreCondition {
+    /// `if_env = "VAR"`: skipped if the environment variable is set.
+    IfEnv(&'static str),
+    /// `unless_env = "VAR"`: skipped unless the environment variable is set.
+    UnlessEnv(&'static str),
+    /// `if = predicate`: skipped if the function returns `true`.
+    If {
+        /// Path of the predicate as written in the attribute.
+        name: &'static str,
+        predicate: fn() -> bool,
+    },
+}
+
+#[doc(hidden)]
+pub const SKIP_MARKER: &str = "satchel::skip: ";
+
+/// Stops the running test and asks the harness to report it as skipped rather than passed,
+/// e.g. when a resource it needs turns out to be unavailable.
+///
+/// The test is unwound with a panic whose message starts with a marker; harnesses recognize
+/// it with [`skip_reason`]. The arguments are an optional reason, formatted like [`panic!`].
+///
+/// # Examples
+/// ```no_run
+/// use satchel::{skip, test};
+///
+/// #[test]
+/// fn talks_to_the_device() {
+///     let connected = false;
+///     if !connected {
+///         skip!("no device connected");
+///     }
+/// }
+/// ```
+#[macro_export]
+macro_rules! skip {
+    () => {
+        ::core::panic!("{}", $crate::SKIP_MARKER)
+    };
+    ($($arg:tt)+) => {
+        ::core::panic!("{}{}", $crate::SKIP_MARKER, ::core::format_args!($($arg)+))
+    };
+}
+
+/// Recognizes the panic raised by [`skip!`] from its message: returns the reason, which is
+/// empty if none was given, or `None` for any other panic.
+pub fn skip_reason(panic_message: &str) -> Option<&str> {
+    panic_message.strip_prefix(SKIP_MARKER)
+}
+
+/// Position of the annotated function in its source file.
+///
+/// Captured by the macros from the span of the function name, so harnesses can point reports
+/// and IDE links at the test definition.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SourceLocation {
+    /// Path of the source file, as reported by `file!()`.
+    pub file: &'static str,
+    /// One-based line number, as reported by `line!()`.
+    pub line: u32,
+    /// One-based column number, as reported by `column!()`.
+    pub column: u32,
+}
+
+impl core::fmt::Display for SourceLocation {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "{}:{}:{}", self.file, self.line, self.column)
+    }
 }
 
 /// Static description of a registered test or benchmark.
@@ -77,26 +444,171 @@ pub struct TestCase {
     pub name: &'static str,
     /// Fully qualified module path for the test function.
     pub module_path: &'static str,
+    /// Source position of the test function.
+    pub location: SourceLocation,
     /// Kind of case (unit test or benchmark).
     pub kind: TestKind,
     /// Entry point invoked by the harness.
-    pub test_fn: TestFn,
+    pub test_fn: EntryPoint,
     /// Panic expectations attached via `#[should_panic]`.
     pub should_panic: Option<ShouldPanic>,
     /// Optional ignore flag populated from `#[ignore]`.
     pub ignore: Option<Ignore>,
-    /// Additional markers supplied through `#[test(...)]` or `#[bench(...)]`.
-    pub case_attributes: &'static [&'static str],
+    /// Additional markers and `key = value` settings supplied through `#[test(...)]` or
+    /// `#[bench(...)]`, in source order. Read them with [`TestCase::attr`] and its typed
+    /// variants.
+    pub case_attributes: &'static [CaseAttribute],
+    /// Tags from `#[test(tags(...))]` and `#[tag(...)]`, without duplicates, for selecting sets
+    /// of cases such as `smoke` or `slow`.
+    pub tags: &'static [&'static str],
+    /// Name of the executor requested via `#[test(executor = "...")]` for `async fn` cases.
+    /// `None` leaves the choice to the harness.
+    pub executor: Option<&'static str>,
+    /// Maximum run time requested via `#[test(timeout = "...")]`. Enforcement is up to the
+    /// harness.
+    pub timeout: Option<core::time::Duration>,
+    /// Number of extra attempts a failing test gets, requested via `#[test(retries = N)]`.
+    /// Enforcement is up to the harness.
+    pub retries: u32,
+    /// Group requested via `#[test(serial_group = "...")]`, or `"default"` for
+    /// `#[test(serial)]`. Harnesses must not run two cases of the same group at the same time.
+    pub serial_group: Option<&'static str>,
+    /// Tests that must pass before this one, requested via `#[test(depends_on = [...])]`, named
+    /// as paths from the module of the test. Read them as full names with
+    /// [`TestCase::dependencies`]. Enforcement is up to the harness.
+    pub depends_on: &'static [&'static str],
+    /// Fixtures requested by the parameters of the test, in parameter order. The harness sets
+    /// them up and passes their values to [`EntryPoint::run_with_fixtures`].
+    pub fixtures: &'static [&'static Fixture],
+}
+
+impl TestCase {
+    /// Value of the case attribute called `key`, if the case has one.
+    pub fn attr(&self, key: &str) -> Option<&'static AttributeValue> {
+        CaseAttribute::find(self.case_attributes, key)
+    }
+
+    /// Whether the case has an attribute called `key`, e.g. the flag `retry_on_failure`.
+    pub fn has_attr(&self, key: &str) -> bool {
+        self.attr(key).is_some()
+    }
+
+    /// Text of `key = "..."`.
+    pub fn attr_str(&self, key: &str) -> Option<&'static str> {
+        self.attr(key)?.as_str()
+    }
+
+    /// Number of `key = 4`.
+    pub fn attr_int(&self, key: &str) -> Option<i64> {
+        self.attr(key)?.as_int()
+    }
+
+    /// Value of `key = true` or `key = false`.
+    pub fn attr_bool(&self, key: &str) -> Option<bool> {
+        self.attr(key)?.as_bool()
+    }
+
+    /// Number of `key = 0.5`, or of `key = 4` as `f64`.
+    pub fn attr_float(&self, key: &str) -> Option<f64> {
+        self.attr(key)?.as_float()
+    }
+
+    /// Full names (`module_path::name`) of the tests in [`TestCase::depends_on`], with
+    /// `crate::`, `self::` and `super::` prefixes resolved.
+    pub fn dependencies(&self) -> impl Iterator<Item = String> + '_ {
+        self.depends_on
+            .iter()
+            .map(|name| resolve_dependency(self.module_path, name))
+    }
+}
+
+fn resolve_dependency(module_path: &str, name: &str) -> String {
+    let (mut base, mut rest) = match name.strip_prefix("crate::") {
+        Some(rest) => (extract_crate_name(module_path), rest),
+        None => (module_path, name.strip_prefix("self::").unwrap_or(name)),
+    };
+    while let Some(path) = rest.strip_prefix("super::") {
+        base = base.rsplit_once("::").map_or("", |(parent, _)| parent);
+        rest = path;
+    }
+    format!("{}::{}", base, rest)
 }
 
 /// Distributed slice exposing registered cases to harness implementations.
 pub mod test_harness {
-    pub use crate::{Ignore, ShouldPanic, TestCase};
+    pub use crate::{
+        AttributeValue, CaseAttribute, Fixture, Ignore, IgnoreCondition, ShouldPanic,
+        SourceLocation, TestCase,
+    };
     use linkme::distributed_slice;
 
     #[doc(hidden)]
     #[distributed_slice]
     pub static TESTS: [TestCase];
+
+    #[doc(hidden)]
+    #[distributed_slice]
+    pub static FIXTURES: [Fixture];
+}
+
+/// Read-only view over every case registered in the linked binary, across all crates.
+///
+/// Obtained through [`registry`]. Harnesses that intentionally run tests from several linked
+/// crates can select them explicitly with [`Registry::for_crate`] and [`Registry::for_module`];
+/// [`get_tests!`] is a shorthand for the cases of the calling crate.
+#[derive(Debug, Clone, Copy)]
+pub struct Registry {
+    cases: &'static [TestCase],
+    fixtures: &'static [Fixture],
+}
+
+/// Returns the [`Registry`] of all cases registered via [`macro@test`] and [`macro@bench`].
+pub fn registry() -> Registry {
+    Registry {
+        cases: &test_harness::TESTS,
+        fixtures: &test_harness::FIXTURES,
+    }
+}
+
+impl Registry {
+    /// All registered cases, in link order.
+    pub fn cases(self) -> &'static [TestCase] {
+        self.cases
+    }
+
+    /// All fixtures registered via [`macro@fixture`], in link order.
+    pub fn fixtures(self) -> &'static [Fixture] {
+        self.fixtures
+    }
+
+    /// Iterates over all registered cases.
+    pub fn iter(self) -> core::slice::Iter<'static, TestCase> {
+        self.cases.iter()
+    }
+
+    /// Cases whose module path starts with the crate segment `crate_name`.
+    ///
+    /// The name must match exactly as it appears in `module_path!()` (hyphens replaced by
+    /// underscores), so `some` does not select cases from `somelib`.
+    pub fn for_crate(self, crate_name: &str) -> impl Iterator<Item = &'static TestCase> {
+        self.iter()
+            .filter(move |case| extract_crate_name(case.module_path) == crate_name)
+    }
+
+    /// Cases defined in `module_path` or any of its submodules, e.g. `mycrate::tests`.
+    pub fn for_module(self, module_path: &str) -> impl Iterator<Item = &'static TestCase> {
+        self.iter()
+            .filter(move |case| is_within_module(case.module_path, module_path))
+    }
+}
+
+impl IntoIterator for Registry {
+    type Item = &'static TestCase;
+    type IntoIter = core::slice::Iter<'static, TestCase>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
 }
 
 #[doc(hidden)]
@@ -107,16 +619,20 @@ pub fn extract_crate_name(module_path: &str) -> &str {
         .expect("Split never returns a empty iterator")
 }
 
+fn is_within_module(module_path: &str, parent: &str) -> bool {
+    match module_path.strip_prefix(parent) {
+        Some(rest) => rest.is_empty() || rest.starts_with("::"),
+        None => false,
+    }
+}
+
 #[doc(hidden)]
 pub fn get_tests_for_crate(crate_prefix: &str) -> impl Iterator<Item = &'static TestCase> {
-    let crate_name = extract_crate_name(crate_prefix);
-    test_harness::TESTS
-        .iter()
-        .filter(move |case| case.module_path.starts_with(crate_name))
+    registry().for_crate(extract_crate_name(crate_prefix))
 }
 
 #[macro_export]
-#[doc = "Returns an iterator over the [`TestCase`] entries belonging to the current crate."]
+#[doc = "Returns an iterator over the [`TestCase`] entries belonging to the current crate.\n\nEquivalent to `satchel::registry().for_crate(...)` with the name of the calling crate; use [`registry`] to select cases from other linked crates."]
 macro_rules! get_tests {
     () => {
         ::satchel::get_tests_for_crate(::core::module_path!())
@@ -125,7 +641,18 @@ macro_rules! get_tests {
 
 #[cfg(test)]
 mod tests {
-    use super::extract_crate_name;
+    use super::{
+        DefaultExecutor, EntryPoint, Executor, Registry, SourceLocation, TestCase, TestKind,
+        extract_crate_name, is_within_module, resolve_dependency,
+    };
+    use alloc::boxed::Box;
+    use alloc::sync::Arc;
+    use alloc::vec::Vec;
+    use core::future::Future;
+    use core::pin::Pin;
+    use core::sync::atomic::{AtomicBool, Ordering};
+    use core::task::{Context, Poll};
+    use core::time::Duration;
 
     #[test]
     fn handles_empty_string() {
@@ -142,4 +669,108 @@ mod tests {
         assert_eq!(extract_crate_name("mycrate::foo"), "mycrate");
         assert_eq!(extract_crate_name("mycrate::foo::bar"), "mycrate");
     }
+
+    #[test]
+    fn crate_names_match_whole_segments() {
+        const fn case(name: &'static str, module_path: &'static str) -> TestCase {
+            TestCase {
+                name,
+                module_path,
+                location: SourceLocation {
+                    file: "src/lib.rs",
+                    line: 1,
+                    column: 1,
+                },
+                kind: TestKind::Unit,
+                test_fn: EntryPoint::Plain(|| {}),
+                should_panic: None,
+                ignore: None,
+                case_attributes: &[],
+                tags: &[],
+                executor: None,
+                timeout: None,
+                retries: 0,
+                serial_group: None,
+                depends_on: &[],
+                fixtures: &[],
+            }
+        }
+        static CASES: [TestCase; 2] = [
+            case("in_somelib", "somelib::tests"),
+            case("in_some", "some::tests"),
+        ];
+        let registry = Registry {
+            cases: &CASES,
+            fixtures: &[],
+        };
+
+        let names = |crate_name| {
+            registry
+                .for_crate(crate_name)
+                .map(|case| case.name)
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(names("some"), ["in_some"]);
+        assert_eq!(names("somelib"), ["in_somelib"]);
+    }
+
+    #[test]
+    fn module_matching_respects_segment_boundaries() {
+        assert!(is_within_module("mycrate::tests", "mycrate::tests"));
+        assert!(is_within_module("mycrate::tests::nested", "mycrate::tests"));
+        assert!(is_within_module("mycrate::tests", "mycrate"));
+        assert!(!is_within_module("mycrate::tests_extra", "mycrate::tests"));
+        assert!(!is_within_module("mycrate2::tests", "mycrate"));
+    }
+
+    #[test]
+    fn dependencies_resolve_from_the_module_of_the_test() {
+        let resolve = |name| resolve_dependency("mycrate::db::tests", name);
+        assert_eq!(resolve("migrate"), "mycrate::db::tests::migrate");
+        assert_eq!(resolve("self::migrate"), "mycrate::db::tests::migrate");
+        assert_eq!(resolve("setup::seed"), "mycrate::db::tests::setup::seed");
+        assert_eq!(resolve("super::connect"), "mycrate::db::connect");
+        assert_eq!(resolve("super::super::init"), "mycrate::init");
+        assert_eq!(resolve("crate::init"), "mycrate::init");
+        assert_eq!(
+            resolve("add::small_numbers"),
+            "mycrate::db::tests::add::small_numbers"
+        );
+    }
+
+    /// Pending until a thread it spawned on the first poll wakes it.
+    struct WokenLater {
+        spawned: bool,
+        done: Arc<AtomicBool>,
+    }
+
+    impl Future for WokenLater {
+        type Output = Result<(), super::TestError>;
+
+        fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+            if self.done.load(Ordering::SeqCst) {
+                return Poll::Ready(Ok(()));
+            }
+            if !self.spawned {
+                self.spawned = true;
+                let done = self.done.clone();
+                let waker = cx.waker().clone();
+                std::thread::spawn(move || {
+                    std::thread::sleep(Duration::from_millis(50));
+                    done.store(true, Ordering::SeqCst);
+                    waker.wake();
+                });
+            }
+            Poll::Pending
+        }
+    }
+
+    #[test]
+    fn default_executor_sleeps_until_the_future_is_woken() {
+        let future = WokenLater {
+            spawned: false,
+            done: Default::default(),
+        };
+        assert!(DefaultExecutor.block_on(Box::pin(future)).is_ok());
+    }
 }
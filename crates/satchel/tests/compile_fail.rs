@@ -5,4 +5,19 @@ fn compile_fail() {
     let t = trybuild::TestCases::new();
     t.compile_fail("tests/compile_fail/ignore_unsupported_forms.rs");
     t.compile_fail("tests/compile_fail/should_panic_unsupported_forms.rs");
+    t.compile_fail("tests/compile_fail/should_panic_result.rs");
+    t.compile_fail("tests/compile_fail/executor_unsupported_forms.rs");
+    t.compile_fail("tests/compile_fail/test_case_unsupported_forms.rs");
+    t.compile_fail("tests/compile_fail/test_case_foreign_attribute.rs");
+    t.compile_fail("tests/compile_fail/timeout_unsupported_forms.rs");
+    t.compile_fail("tests/compile_fail/retries_unsupported_forms.rs");
+    t.compile_fail("tests/compile_fail/serial_unsupported_forms.rs");
+    t.compile_fail("tests/compile_fail/depends_on_unsupported_forms.rs");
+    t.compile_fail("tests/compile_fail/depends_on_unknown_test.rs");
+    t.compile_fail("tests/compile_fail/depends_on_not_a_test.rs");
+    t.compile_fail("tests/compile_fail/fixture_unsupported_forms.rs");
+    t.compile_fail("tests/compile_fail/case_attribute_unsupported_forms.rs");
+    t.compile_fail("tests/compile_fail/tag_unsupported_forms.rs");
+    t.compile_fail("tests/compile_fail/tag_foreign_attribute.rs");
+    t.compile_fail("tests/compile_fail/bencher_unsupported_forms.rs");
 }
@@ -0,0 +1,27 @@
+mod common;
+use satchel::{Bencher, bench, fixture, test};
+
+// Only benchmarks take a Bencher
+#[test]
+fn test_with_bencher(b: &mut Bencher) {
+    b.iter(|| ());
+}
+
+// Benchers are not passed to async benchmarks
+#[bench]
+async fn async_with_bencher(b: &mut Bencher<'_>) {
+    b.iter(|| ());
+}
+
+#[fixture]
+fn number() -> u32 {
+    1
+}
+
+// A Bencher cannot be combined with fixtures
+#[bench]
+fn bencher_with_fixture(b: &mut Bencher, number: &u32) {
+    b.iter(|| *number);
+}
+
+fn main() {}
@@ -0,0 +1,28 @@
+mod common;
+use satchel::test;
+
+// Values must be literals
+#[test(threads = 2 + 2)]
+fn expression_value() {}
+
+// Arrays are not supported
+#[test(modes = ["smoke", "full"])]
+fn array_value() {}
+
+// Only numbers can be negated
+#[test(name = -"x")]
+fn negated_string() {}
+
+// Integers must fit into i64
+#[test(limit = 9_223_372_036_854_775_808)]
+fn integer_overflow() {}
+
+// A key may only be given once
+#[test(threads = 2, threads = 4)]
+fn duplicate_key() {}
+
+// Neither a marker nor a key = value pair
+#[test(1)]
+fn bare_number() {}
+
+fn main() {}
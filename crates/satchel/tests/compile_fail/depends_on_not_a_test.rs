@@ -0,0 +1,32 @@
+mod common;
+use satchel::{bench, test, test_case};
+
+fn connect() {}
+
+const MIGRATE_SCHEMA: () = ();
+
+#[bench]
+fn query_speed() {}
+
+#[test_case(1, 2 => 3)]
+fn add(a: u32, b: u32) -> u32 {
+    a + b
+}
+
+// Paths must name a #[test] function, not a plain function
+#[test(depends_on = [connect])]
+fn plain_function() {}
+
+// ... nor a constant
+#[test(depends_on = [MIGRATE_SCHEMA])]
+fn constant() {}
+
+// ... nor a benchmark
+#[test(depends_on = [query_speed])]
+fn benchmark() {}
+
+// ... nor a #[test_case] function, whose rows are named by string literals
+#[test(depends_on = [add])]
+fn test_case_function() {}
+
+fn main() {}
@@ -0,0 +1,11 @@
+mod common;
+use satchel::test;
+
+#[test]
+fn migrate_schema() {}
+
+// Paths must name a function in scope
+#[test(depends_on = [migrate_shcema])]
+fn query_users() {}
+
+fn main() {}
@@ -0,0 +1,30 @@
+mod common;
+use satchel::test;
+
+#[test]
+fn migrate_schema() {}
+
+// Dependencies are listed in brackets
+#[test(depends_on = migrate_schema)]
+fn not_a_list() {}
+
+// An empty list
+#[test(depends_on = [])]
+fn empty_list() {}
+
+// Numbers and empty strings name no test
+#[test(depends_on = [1])]
+fn number() {}
+
+#[test(depends_on = [""])]
+fn empty_name() {}
+
+// Paths name functions, without generic arguments
+#[test(depends_on = [migrate_schema::<u8>])]
+fn generic_path() {}
+
+// The list is given once
+#[test(depends_on = [migrate_schema], depends_on = [migrate_schema])]
+fn two_lists() {}
+
+fn main() {}
@@ -0,0 +1,16 @@
+mod common;
+use satchel::test;
+
+// Executors only apply to async functions
+#[test(executor = "tokio")]
+fn executor_on_sync_fn() {}
+
+// Executor name must be a string literal
+#[test(executor = tokio)]
+async fn executor_not_a_string() {}
+
+// Executor may only be selected once
+#[test(executor = "a", executor = "b")]
+async fn duplicate_executor() {}
+
+fn main() {}
@@ -0,0 +1,38 @@
+mod common;
+use satchel::{fixture, test};
+
+// Unknown scope
+#[fixture(scope = "session")]
+fn unknown_scope() -> u32 {
+    1
+}
+
+// Not a key = value pair
+#[fixture(module)]
+fn bare_scope() -> u32 {
+    1
+}
+
+// Fixtures cannot take parameters
+#[fixture]
+fn with_param(seed: u32) -> u32 {
+    seed
+}
+
+// Fixtures cannot be async
+#[fixture]
+async fn async_fixture() -> u32 {
+    1
+}
+
+// Fixtures must return their value
+#[fixture]
+fn returns_nothing() {}
+
+// Fixtures are requested by shared reference
+#[test]
+fn by_value(unknown_scope: u32) {
+    let _ = unknown_scope;
+}
+
+fn main() {}
@@ -22,4 +22,28 @@ fn ignored_non_string() {}
 #[ignore = "reason"]
 fn duplicate_ignore() {}
 
+// A reason alone is not a condition
+#[test]
+#[ignore(reason = "slow")]
+fn reason_without_condition() {}
+
+// Only one condition per #[ignore]
+#[test]
+#[ignore(if_env = "CI", unless_env = "HAVE_DB")]
+fn two_conditions() {}
+
+// Environment variable names are string literals
+#[test]
+#[ignore(if_env = CI)]
+fn unquoted_env_var() {}
+
+fn is_ci() -> bool {
+    true
+}
+
+// The predicate must be a path to a function
+#[test]
+#[ignore(if = is_ci())]
+fn predicate_call() {}
+
 fn main() {}
\ No newline at end of file
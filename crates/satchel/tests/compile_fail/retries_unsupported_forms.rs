@@ -0,0 +1,20 @@
+mod common;
+use satchel::test;
+
+// Negative retries
+#[test(retries = -1)]
+fn negative_retries() {}
+
+// Not a number
+#[test(retries = "3")]
+fn string_retries() {}
+
+// Fractions of an attempt
+#[test(retries = 1.5)]
+fn fractional_retries() {}
+
+// Retries may only be given once
+#[test(retries = 1, retries = 2)]
+fn duplicate_retries() {}
+
+fn main() {}
@@ -0,0 +1,20 @@
+mod common;
+use satchel::test;
+
+// An empty group name
+#[test(serial_group = "")]
+fn empty_group() {}
+
+// The group must be a string literal
+#[test(serial_group = db)]
+fn identifier_group() {}
+
+// Numbers are not group names
+#[test(serial_group = 1)]
+fn number_group() {}
+
+// A case belongs to one group at most
+#[test(serial, serial_group = "db")]
+fn two_groups() {}
+
+fn main() {}
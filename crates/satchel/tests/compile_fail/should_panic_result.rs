@@ -0,0 +1,11 @@
+mod common;
+use satchel::test;
+
+// should_panic cannot be combined with a Result return type
+#[test]
+#[should_panic]
+fn should_panic_with_result() -> Result<(), String> {
+    Ok(())
+}
+
+fn main() {}
@@ -0,0 +1,9 @@
+mod common;
+use satchel::test;
+
+// `tag` attributes of other crates are kept on the function
+#[test]
+#[other_crate::tag(smoke)]
+fn foreign_tag() {}
+
+fn main() {}
@@ -0,0 +1,27 @@
+mod common;
+use satchel::test;
+
+// Tags are identifiers or string literals
+#[test(tags(1))]
+fn numeric_tag() {}
+
+// Tags cannot contain spaces or operators
+#[test(tags("needs gpu"))]
+fn tag_with_space() {}
+
+// At least one tag is required
+#[test]
+#[tag()]
+fn empty_tag_list() {}
+
+// #[tag] needs a list
+#[test]
+#[tag = "smoke"]
+fn tag_name_value() {}
+
+// #[tag] only works below #[test]
+#[satchel::tag(smoke)]
+#[test]
+fn tag_above_test() {}
+
+fn main() {}
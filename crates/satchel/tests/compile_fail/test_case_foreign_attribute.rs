@@ -0,0 +1,9 @@
+mod common;
+use satchel::test_case;
+
+// `test_case` attributes of other crates are kept on the function
+#[test_case(1)]
+#[other_crate::test_case(2)]
+fn foreign_row(_value: i32) {}
+
+fn main() {}
@@ -0,0 +1,19 @@
+mod common;
+use satchel::test_case;
+
+// Argument count must match the function signature
+#[test_case(1 => 1)]
+fn wrong_arity(left: u32, right: u32) -> u32 {
+    left + right
+}
+
+// Labels must be unique once sanitized
+#[test_case(1; label = "same name")]
+#[test_case(2; label = "Same  Name")]
+fn duplicate_label(_value: u32) {}
+
+// Labels must be string literals
+#[test_case(1; label = one)]
+fn label_not_a_string(_value: u32) {}
+
+fn main() {}
@@ -0,0 +1,24 @@
+mod common;
+use satchel::test;
+
+// Unknown unit
+#[test(timeout = "30 days")]
+fn unknown_unit() {}
+
+// Missing unit
+#[test(timeout = "30")]
+fn missing_unit() {}
+
+// Zero is not a usable timeout
+#[test(timeout = "0s")]
+fn zero_timeout() {}
+
+// Not a duration at all
+#[test(timeout = true)]
+fn not_a_duration() {}
+
+// Timeout may only be given once
+#[test(timeout = "1s", timeout = "2s")]
+fn duplicate_timeout() {}
+
+fn main() {}
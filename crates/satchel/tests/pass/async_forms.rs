@@ -0,0 +1,26 @@
+// async fn tests are accepted with and without a return type and an executor selection.
+use satchel::{bench, test};
+
+fn main() {}
+
+#[test]
+async fn plain_async() {}
+
+#[test]
+async fn async_result() -> Result<(), std::num::ParseIntError> {
+    let value: u8 = "7".parse()?;
+    assert_eq!(value, 7);
+    Ok(())
+}
+
+#[test(executor = "tokio")]
+async fn named_executor() {}
+
+#[test("--flag", executor = "custom")]
+#[should_panic(expected = "boom")]
+async fn async_should_panic() {
+    panic!("boom");
+}
+
+#[bench]
+async fn async_bench() {}
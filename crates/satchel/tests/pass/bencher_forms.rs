@@ -0,0 +1,34 @@
+// Supported #[bench] forms taking a Bencher.
+use satchel::{Bencher, Throughput, bench};
+
+fn main() {}
+
+#[bench]
+fn iter(b: &mut Bencher) {
+    b.iter(|| (0..100u64).sum::<u64>());
+}
+
+#[bench]
+fn iter_batched_with_throughput(bencher: &mut Bencher<'_>) {
+    let data: Vec<u8> = vec![7; 4096];
+    bencher.set_throughput(Throughput::Bytes(data.len() as u64));
+    bencher.iter_batched(|| data.clone(), |mut data| data.sort());
+}
+
+#[bench]
+fn qualified_path(b: &mut satchel::Bencher) {
+    b.iter(|| ());
+}
+
+#[bench]
+fn returns_result(b: &mut Bencher) -> Result<(), String> {
+    let input: u32 = "42".parse().map_err(|e| format!("{:?}", e))?;
+    b.iter(|| input * 2);
+    Ok(())
+}
+
+#[bench(timeout = "10s", "fast")]
+#[ignore]
+fn with_attributes(b: &mut Bencher) {
+    b.iter(|| ());
+}
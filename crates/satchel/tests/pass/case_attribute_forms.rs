@@ -0,0 +1,39 @@
+// Supported case attribute forms in #[test(...)], #[bench(...)] and #[test_case(...)].
+use satchel::{bench, test, test_case};
+
+fn main() {}
+
+#[test("--raw", flag, custom::MARKER)]
+fn flags_and_raw_strings() {}
+
+#[test(threads = 4, mode = "smoke", enabled = true, ratio = 0.25)]
+fn typed_settings() {}
+
+#[test(offset = -3, scale = -1.5, limit = 9_223_372_036_854_775_807)]
+fn negative_and_large_numbers() {}
+
+#[test(custom::level = 2, flaky, timeout = "1s")]
+fn path_keys_with_other_options() {}
+
+#[bench(iterations = 100)]
+fn bench_with_setting() {}
+
+#[test_case(1; threads = 2, "--raw")]
+#[test_case(2; label = "two", mode = "full")]
+fn rows_with_settings(_value: u32) {}
+
+#[test(tags(smoke, "needs-gpu-stub"), threads = 2)]
+fn tags_in_test() {}
+
+#[test]
+#[satchel::tag(slow)]
+#[satchel::tag(nightly, "team.storage")]
+fn tag_attributes() {}
+
+#[bench(tags(slow))]
+fn bench_with_tags() {}
+
+#[test_case(1; tags(fast))]
+#[test_case(2)]
+#[satchel::tag(table)]
+fn rows_with_tags(_value: u32) {}
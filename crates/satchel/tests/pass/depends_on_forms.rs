@@ -0,0 +1,42 @@
+// Supported #[test(depends_on = [...])] forms.
+use satchel::{test, test_case};
+
+fn main() {}
+
+#[test]
+fn migrate_schema() {}
+
+#[test(depends_on = [migrate_schema])]
+fn query_users() {}
+
+#[test(depends_on = [migrate_schema, self::query_users, "add::small_numbers"], serial)]
+fn report() {}
+
+#[test(depends_on = [crate::migrate_schema])]
+async fn from_the_crate_root() {}
+
+#[test_case(1, 2 => 3; label = "small_numbers")]
+fn add(a: u32, b: u32) -> u32 {
+    a + b
+}
+
+#[test_case(1; depends_on = [migrate_schema])]
+fn row_with_dependency(_value: u32) {}
+
+mod nested {
+    use satchel::test;
+
+    #[test(depends_on = [super::migrate_schema])]
+    fn from_the_parent_module() {}
+}
+
+mod db {
+    use satchel::test;
+
+    // Visible to the sibling module, like the function.
+    #[test]
+    pub(crate) fn connect() {}
+}
+
+#[test(depends_on = [db::connect])]
+fn from_a_sibling_module() {}
@@ -0,0 +1,70 @@
+// Supported #[fixture] forms and tests requesting them.
+use satchel::{fixture, test};
+
+fn main() {}
+
+pub struct Database {
+    pub url: String,
+}
+
+#[fixture]
+fn database() -> Database {
+    Database {
+        url: "sqlite::memory:".to_string(),
+    }
+}
+
+#[fixture(scope = "module")]
+fn port() -> u16 {
+    8080
+}
+
+#[fixture(scope = "run")]
+fn config() -> Result<Vec<String>, std::io::Error> {
+    Ok(vec!["verbose".to_string()])
+}
+
+#[fixture(scope = "test")]
+pub fn counter() -> std::sync::atomic::AtomicUsize {
+    std::sync::atomic::AtomicUsize::new(0)
+}
+
+#[test]
+fn uses_one(database: &Database) {
+    assert!(!database.url.is_empty());
+}
+
+#[test]
+fn uses_several(database: &Database, port: &u16, config: &Vec<String>) -> Result<(), String> {
+    let _ = (database, port, config);
+    Ok(())
+}
+
+#[test]
+async fn async_uses_fixture(port: &u16) {
+    assert_eq!(*port, 8080);
+}
+
+mod nested {
+    use super::*;
+    use satchel::{fixture, test};
+
+    #[test]
+    fn imported_fixture(counter: &std::sync::atomic::AtomicUsize) {
+        let _ = counter;
+    }
+
+    // Paths in the signature are relative to the module of the fixture
+    #[fixture]
+    fn replica() -> super::Database {
+        super::database()
+    }
+
+    // An item named like a fixture does not get in the way
+    mod replica {}
+
+    #[test]
+    fn relative_fixture_type(replica: &super::Database) {
+        let _ = &replica.url;
+    }
+}
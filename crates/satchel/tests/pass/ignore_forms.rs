@@ -0,0 +1,65 @@
+// Supported #[ignore] forms and runtime skips.
+use satchel::{bench, skip, test, test_case};
+
+fn main() {}
+
+fn no_display() -> bool {
+    std::env::var_os("DISPLAY").is_none()
+}
+
+mod predicates {
+    pub fn always() -> bool {
+        true
+    }
+}
+
+#[test]
+#[ignore]
+fn bare() {}
+
+#[test]
+#[ignore = "slow"]
+fn with_reason() {}
+
+#[test]
+#[ignore(if_env = "CI")]
+fn if_env() {}
+
+#[test]
+#[ignore(unless_env = "HAVE_DB", reason = "needs a database")]
+fn unless_env_with_reason() {}
+
+#[test]
+#[ignore(if = no_display)]
+fn if_predicate() {}
+
+#[test]
+#[ignore(reason = "always skipped", if = predicates::always)]
+fn reason_before_qualified_predicate() {}
+
+#[test]
+#[cfg_attr(not(target_os = "linux"), ignore = "linux only")]
+fn cfg_attr_ignore() {}
+
+#[bench]
+#[ignore(if_env = "CI")]
+fn bench_if_env() {}
+
+#[test_case(1)]
+#[test_case(2)]
+#[ignore(unless_env = "HAVE_DB")]
+fn rows_unless_env(_value: u32) {}
+
+#[test]
+fn skips_without_reason() {
+    skip!();
+}
+
+#[test]
+fn skips_with_formatted_reason() -> Result<(), String> {
+    let device = "gpu0";
+    if device.is_empty() {
+        return Ok(());
+    }
+    skip!("{} is not available", device);
+}
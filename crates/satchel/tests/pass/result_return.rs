@@ -0,0 +1,32 @@
+// Tests returning Result<(), E: Debug> are accepted alongside plain fn() tests.
+use satchel::{bench, test};
+
+fn main() {}
+
+#[test]
+fn returns_ok() -> Result<(), String> {
+    Ok(())
+}
+
+#[test]
+fn uses_question_mark() -> Result<(), std::num::ParseIntError> {
+    let value: u32 = "42".parse()?;
+    assert_eq!(value, 42);
+    Ok(())
+}
+
+#[test]
+fn boxed_error() -> Result<(), Box<dyn std::error::Error>> {
+    Ok(())
+}
+
+#[test]
+#[ignore = "still reported through the wrapper"]
+fn ignored_result() -> Result<(), ()> {
+    Err(())
+}
+
+#[bench]
+fn bench_returns_result() -> Result<(), String> {
+    Ok(())
+}
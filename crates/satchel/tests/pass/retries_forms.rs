@@ -0,0 +1,13 @@
+// Supported #[test(retries = ...)] forms.
+use satchel::{test, test_case};
+
+fn main() {}
+
+#[test(retries = 3)]
+fn three_retries() {}
+
+#[test(retries = 0, timeout = "1s")]
+fn no_retries() {}
+
+#[test_case(1; retries = 2)]
+fn row_with_retries(_value: u32) {}
@@ -0,0 +1,19 @@
+// Supported #[test(serial)] and #[test(serial_group = ...)] forms.
+use satchel::{bench, test, test_case};
+
+fn main() {}
+
+#[test(serial)]
+fn default_group() {}
+
+#[test(serial_group = "db")]
+fn named_group() {}
+
+#[test(serial_group = "db", timeout = "1s")]
+async fn named_group_async() {}
+
+#[bench(serial)]
+fn bench_in_default_group() {}
+
+#[test_case(1; serial_group = "gpu")]
+fn row_in_group(_value: u32) {}
@@ -0,0 +1,34 @@
+// Parameterized #[test_case] rows in their supported forms.
+use satchel::test_case;
+
+fn main() {}
+
+#[test_case(1, 2 => 3)]
+#[test_case(2, 2 => 4; label = "two twos")]
+#[satchel::test_case(0, 0 => 0; "--fast", retry_on_failure)]
+fn add(left: u32, right: u32) -> u32 {
+    left + right
+}
+
+#[test_case("42")]
+#[test_case("7"; label = "single digit")]
+fn parses(input: &str) -> Result<(), std::num::ParseIntError> {
+    input.parse::<u32>().map(|_| ())
+}
+
+#[test_case(0)]
+#[should_panic(expected = "zero")]
+#[ignore = "shared by every row"]
+fn rejects(value: u32) {
+    assert!(value != 0, "zero");
+}
+
+#[test_case(3 => 6; executor = "tokio")]
+async fn doubles(value: u32) -> u32 {
+    value * 2
+}
+
+#[test_case(if true { 1 } else { 2 }, [1, 2].len() => true)]
+fn complex_expressions(left: usize, right: usize) -> bool {
+    left < right
+}
@@ -0,0 +1,25 @@
+// Supported #[test(timeout = ...)] forms.
+use satchel::{bench, test, test_case};
+
+fn main() {}
+
+#[test(timeout = "500ms")]
+fn millis() {}
+
+#[test(timeout = "30s")]
+fn seconds() {}
+
+#[test(timeout = "2m", "--flag")]
+fn minutes_with_attribute() {}
+
+#[test(timeout = "1h")]
+async fn hours_async() {}
+
+#[test(timeout = 5)]
+fn bare_seconds() {}
+
+#[bench(timeout = "1s")]
+fn bench_with_timeout() {}
+
+#[test_case(1; timeout = "100ms")]
+fn row_with_timeout(_value: u32) {}
@@ -0,0 +1,29 @@
+# Invoked as `cmake -D TEST_EXECUTABLE=... -D CTEST_FILE=... -P CheckDiscoveredTests.cmake`:
+# checks that the CTest file written by satchel_discover_tests() adds a test for every case the
+# executable lists, and that case attributes with brackets keep their labels.
+
+execute_process(
+    COMMAND "${TEST_EXECUTABLE}" --list-tests
+    OUTPUT_VARIABLE output
+    RESULT_VARIABLE result
+)
+if(NOT result EQUAL 0)
+    message(FATAL_ERROR "Error listing Satchel tests of ${TEST_EXECUTABLE} (${result})")
+endif()
+file(READ "${CTEST_FILE}" content)
+
+# Counted with regular expressions, as the lines themselves hold brackets.
+string(REGEX MATCHALL "satchel-case\t" listed "${output}")
+string(REGEX MATCHALL "\nadd_test\\(" added "${content}")
+list(LENGTH listed listed_count)
+list(LENGTH added added_count)
+if(NOT listed_count EQUAL added_count)
+    message(FATAL_ERROR
+        "${TEST_EXECUTABLE} lists ${listed_count} cases, but ${CTEST_FILE} adds ${added_count} tests")
+endif()
+
+# `]==]` ends a bracket argument of level 2, so the labels need one of level 3.
+string(FIND "${content}" "LABELS [===[range=[0, 10);note=]==]]===]" at)
+if(at EQUAL -1)
+    message(FATAL_ERROR "${CTEST_FILE} lacks the labels `range=[0, 10)` and `note=]==]`:\n${content}")
+endif()
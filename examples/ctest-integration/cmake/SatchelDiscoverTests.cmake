@@ -0,0 +1,217 @@
+#[=======================================================================[.rst:
+SatchelDiscoverTests
+--------------------
+
+Registers every Satchel ``TestCase`` linked into an executable as its own CTest
+test, analogous to ``gtest_discover_tests()``.
+
+.. code-block:: cmake
+
+  satchel_discover_tests(<target>
+                         [TEST_PREFIX <prefix>]
+                         [EXTRA_ARGS <arg>...]
+                         [PROPERTIES <name> <value>...]
+                         [DISCOVERY_TIMEOUT <seconds>]
+                         [DISCOVERY_MODE <POST_BUILD|PRE_TEST>])
+
+The executable must implement two command lines on top of the C API from
+``satchel_tests.h``:
+
+``<target> --list-tests``
+  Calls ``<prefix>_list_tests()`` of every linked library.
+
+``<target> --run-test <name> [<extra args>...]``
+  Calls ``<prefix>_run_test(name)`` and returns its status. The example host
+  accepts ``--nocapture`` and ``--show-output`` as extra arguments and passes
+  them on with ``<prefix>_set_output()``.
+
+Each case becomes a test named ``<prefix>module_path::name`` that runs
+``<target> --run-test module_path::name``. Cases marked ``#[ignore]`` get the
+``DISABLED`` property. A case that calls ``satchel::skip!`` or whose
+``#[ignore(...)]`` condition holds when it runs exits with ``SATCHEL_SKIPPED``,
+which ``SKIP_RETURN_CODE`` turns into a skipped test. The tags and case
+attributes of a case become its ``LABELS``
+(a ``;`` inside an attribute is replaced by ``,``; square brackets are kept), so
+``ctest -R``, ``-L`` and ``-LE`` select Rust tests like any other.
+
+``TEST_PREFIX``
+  Prepended to the name of every discovered test.
+
+``EXTRA_ARGS``
+  Arguments appended to the command line of every discovered test, e.g.
+  ``--show-output``.
+
+``PROPERTIES``
+  Extra test properties set on every discovered test.
+
+``DISCOVERY_TIMEOUT``
+  Seconds to wait for ``--list-tests``, 5 by default.
+
+``DISCOVERY_MODE``
+  ``POST_BUILD`` (the default) lists the tests right after the target is
+  linked. ``PRE_TEST`` lists them when ``ctest`` runs, which is needed when the
+  executable cannot run on the build machine right after linking.
+#]=======================================================================]
+
+set(_SATCHEL_DISCOVER_TESTS_SCRIPT "${CMAKE_CURRENT_LIST_FILE}")
+
+# Sets `out` to `value` as a bracket argument, with as many '=' as it takes for the closing
+# bracket not to occur in the value, like `]==]` in a case attribute.
+function(_satchel_bracket_argument out value)
+    set(level "==")
+    while("${value}]" MATCHES "]${level}]")
+        string(APPEND level "=")
+    endwhile()
+    set(${out} "[${level}[${value}]${level}]" PARENT_SCOPE)
+endfunction()
+
+if(CMAKE_SCRIPT_MODE_FILE)
+    # Invoked as `cmake -D PARAMS_FILE=... -P SatchelDiscoverTests.cmake`: run the
+    # executable and write the CTest file that adds one test per case.
+    include("${PARAMS_FILE}")
+
+    if(NOT EXISTS "${TEST_EXECUTABLE}")
+        message(FATAL_ERROR "Cannot list Satchel tests: ${TEST_EXECUTABLE} does not exist")
+    endif()
+
+    execute_process(
+        COMMAND "${TEST_EXECUTABLE}" --list-tests
+        WORKING_DIRECTORY "${TEST_WORKING_DIR}"
+        TIMEOUT ${TEST_DISCOVERY_TIMEOUT}
+        OUTPUT_VARIABLE output
+        ERROR_VARIABLE output
+        RESULT_VARIABLE result
+    )
+    if(NOT result EQUAL 0)
+        message(FATAL_ERROR
+            "Error listing Satchel tests of ${TEST_EXECUTABLE} (${result}):\n${output}")
+    endif()
+
+    # Rust test names cannot contain ';', but case attributes can.
+    string(REPLACE ";" "," output "${output}")
+    # Nor can they contain square brackets, which keep CMake from splitting a list at the ';'
+    # inside them, or after an unbalanced '['. They are swapped for placeholders until the
+    # fields are split.
+    set(bracket "__satchel_bracket")
+    while(output MATCHES "${bracket}")
+        string(APPEND bracket "_")
+    endwhile()
+    string(REPLACE "[" "${bracket}open" output "${output}")
+    string(REPLACE "]" "${bracket}close" output "${output}")
+    string(REPLACE "\r" "" output "${output}")
+    string(REPLACE "\n" ";" lines "${output}")
+
+    set(content "# Generated by satchel_discover_tests() from ${TEST_EXECUTABLE}\n")
+    foreach(line IN LISTS lines)
+        if(NOT line MATCHES "^satchel-case\t")
+            continue()
+        endif()
+        string(REPLACE "\t" ";" fields "${line}")
+        list(GET fields 1 name)
+        list(GET fields 3 ignored)
+        list(SUBLIST fields 4 -1 labels)
+        string(REPLACE "${bracket}open" "[" labels "${labels}")
+        string(REPLACE "${bracket}close" "]" labels "${labels}")
+
+        _satchel_bracket_argument(test_name "${TEST_PREFIX}${name}")
+        _satchel_bracket_argument(executable "${TEST_EXECUTABLE}")
+        _satchel_bracket_argument(name "${name}")
+        _satchel_bracket_argument(working_dir "${TEST_WORKING_DIR}")
+        string(APPEND content "add_test(${test_name} ${executable} --run-test ${name}")
+        foreach(arg IN LISTS TEST_EXTRA_ARGS)
+            _satchel_bracket_argument(arg "${arg}")
+            string(APPEND content " ${arg}")
+        endforeach()
+        string(APPEND content ")\n"
+            "set_tests_properties(${test_name} PROPERTIES"
+            " WORKING_DIRECTORY ${working_dir}"
+            " SKIP_RETURN_CODE 5" # SATCHEL_SKIPPED
+        )
+        if(ignored)
+            string(APPEND content " DISABLED TRUE")
+        endif()
+        if(NOT labels STREQUAL "")
+            _satchel_bracket_argument(labels "${labels}")
+            string(APPEND content " LABELS ${labels}")
+        endif()
+        foreach(property IN LISTS TEST_PROPERTIES)
+            _satchel_bracket_argument(property "${property}")
+            string(APPEND content " ${property}")
+        endforeach()
+        string(APPEND content ")\n")
+    endforeach()
+
+    file(WRITE "${CTEST_FILE}" "${content}")
+    return()
+endif()
+
+function(satchel_discover_tests target)
+    cmake_parse_arguments(PARSE_ARGV 1 arg
+        ""
+        "TEST_PREFIX;DISCOVERY_TIMEOUT;DISCOVERY_MODE"
+        "EXTRA_ARGS;PROPERTIES"
+    )
+    if(arg_UNPARSED_ARGUMENTS)
+        message(FATAL_ERROR "satchel_discover_tests: unknown arguments ${arg_UNPARSED_ARGUMENTS}")
+    endif()
+    if(NOT arg_DISCOVERY_TIMEOUT)
+        set(arg_DISCOVERY_TIMEOUT 5)
+    endif()
+    if(NOT arg_DISCOVERY_MODE)
+        set(arg_DISCOVERY_MODE POST_BUILD)
+    endif()
+
+    set(file_base "${CMAKE_CURRENT_BINARY_DIR}/${target}_satchel")
+    set(params_file "${file_base}_params.cmake")
+    set(ctest_file "${file_base}_tests.cmake")
+    set(include_file "${file_base}_include.cmake")
+
+    # Parameters go through a file so lists and paths survive without extra quoting.
+    file(GENERATE OUTPUT "${params_file}" CONTENT
+"set(TEST_EXECUTABLE [==[$<TARGET_FILE:${target}>]==])
+set(TEST_WORKING_DIR [==[${CMAKE_CURRENT_BINARY_DIR}]==])
+set(TEST_PREFIX [==[${arg_TEST_PREFIX}]==])
+set(TEST_EXTRA_ARGS [==[${arg_EXTRA_ARGS}]==])
+set(TEST_PROPERTIES [==[${arg_PROPERTIES}]==])
+set(TEST_DISCOVERY_TIMEOUT [==[${arg_DISCOVERY_TIMEOUT}]==])
+set(CTEST_FILE [==[${ctest_file}]==])
+")
+
+    if(arg_DISCOVERY_MODE STREQUAL "POST_BUILD")
+        add_custom_command(TARGET ${target} POST_BUILD
+            BYPRODUCTS "${ctest_file}"
+            COMMAND "${CMAKE_COMMAND}"
+                -D "PARAMS_FILE=${params_file}"
+                -P "${_SATCHEL_DISCOVER_TESTS_SCRIPT}"
+            VERBATIM
+        )
+        file(WRITE "${include_file}"
+"if(EXISTS [==[${ctest_file}]==])
+    include([==[${ctest_file}]==])
+else()
+    add_test([==[${target}_NOT_BUILT]==] [==[${target}_NOT_BUILT]==])
+endif()
+")
+    elseif(arg_DISCOVERY_MODE STREQUAL "PRE_TEST")
+        file(GENERATE OUTPUT "${include_file}" CONTENT
+"if(NOT EXISTS [==[${ctest_file}]==] OR
+   [==[$<TARGET_FILE:${target}>]==] IS_NEWER_THAN [==[${ctest_file}]==])
+    execute_process(
+        COMMAND [==[${CMAKE_COMMAND}]==]
+            -D [==[PARAMS_FILE=${params_file}]==]
+            -P [==[${_SATCHEL_DISCOVER_TESTS_SCRIPT}]==]
+        RESULT_VARIABLE result
+    )
+    if(NOT result EQUAL 0)
+        add_test([==[${target}_DISCOVERY_FAILED]==] [==[${target}_DISCOVERY_FAILED]==])
+    endif()
+endif()
+include([==[${ctest_file}]==] OPTIONAL)
+")
+    else()
+        message(FATAL_ERROR
+            "satchel_discover_tests: DISCOVERY_MODE must be POST_BUILD or PRE_TEST, not ${arg_DISCOVERY_MODE}")
+    endif()
+
+    set_property(DIRECTORY APPEND PROPERTY TEST_INCLUDE_FILES "${include_file}")
+endfunction()
@@ -1,11 +1,72 @@
 #include <algorithm>
+#include <cstdio>
+#include <cstring>
 #include <vector>
 
+#include "satchel_tests.h"
+
 extern "C" int some_tests_main();
 extern "C" int other_tests_main();
 
-int main()
+SATCHEL_DECLARE_TESTS(somelib);
+SATCHEL_DECLARE_TESTS(otherlib);
+
+struct TestLibrary
+{
+    int (*list)();
+    int (*run)(const char *);
+    int (*setOutput)(int);
+};
+
+static const TestLibrary libraries[] = {
+    {somelib_list_tests, somelib_run_test, somelib_set_output},
+    {otherlib_list_tests, otherlib_run_test, otherlib_set_output},
+};
+
+// Prints the cases of all libraries in the format read by satchel_discover_tests().
+static int listTests()
+{
+    for (const TestLibrary &library : libraries) {
+        if (library.list() != SATCHEL_PASSED)
+            return 1;
+    }
+    return 0;
+}
+
+static int runTest(const char *name, int outputMode)
+{
+    for (const TestLibrary &library : libraries) {
+        library.setOutput(outputMode);
+    }
+    for (const TestLibrary &library : libraries) {
+        const int status = library.run(name);
+        if (status != SATCHEL_NOT_FOUND)
+            return status;
+    }
+    std::fprintf(stderr, "no test named %s\n", name);
+    return SATCHEL_NOT_FOUND;
+}
+
+int main(int argc, char **argv)
 {
+    if (argc == 2 && std::strcmp(argv[1], "--list-tests") == 0)
+        return listTests();
+    if (argc >= 3 && std::strcmp(argv[1], "--run-test") == 0) {
+        // Like the flags of the Rust test harness: --nocapture or --show-output.
+        int outputMode = SATCHEL_OUTPUT_CAPTURE;
+        for (int i = 3; i < argc; ++i) {
+            if (std::strcmp(argv[i], "--nocapture") == 0) {
+                outputMode = SATCHEL_OUTPUT_NOCAPTURE;
+            } else if (std::strcmp(argv[i], "--show-output") == 0) {
+                outputMode = SATCHEL_OUTPUT_SHOW;
+            } else {
+                std::fprintf(stderr, "unknown option %s\n", argv[i]);
+                return SATCHEL_INVALID_ARGUMENT;
+            }
+        }
+        return runTest(argv[2], outputMode);
+    }
+
     std::vector<int> results;
 
     results.push_back(some_tests_main());
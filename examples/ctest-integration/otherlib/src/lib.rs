@@ -1,11 +1,15 @@
-use libtest_mimic::Arguments;
-use test_runner;
+#![allow(clippy::identity_op)]
 
+/// Entry point called by the C++ test driver.
+///
+/// # Safety
+///
+/// Call it once, from the `main` of the test driver.
 #[unsafe(no_mangle)]
 pub unsafe extern "C" fn other_tests_main() -> i32 {
     println!("Running tests in {}", std::module_path!());
     let tests = satchel::get_tests!();
-    let args = Arguments::from_args();
+    let args = test_runner::RunnerArgs::from_args();
 
     if test_runner::run_tests(tests, args) {
         0
@@ -14,18 +18,20 @@ pub unsafe extern "C" fn other_tests_main() -> i32 {
     }
 }
 
+test_runner::export_c_api!(otherlib);
+
 pub fn add(left: u64, right: u64) -> u64 {
     left + right
 }
 
 mod tests {
     use super::*;
-    use satchel::{bench, test};
+    use satchel::{bench, black_box, test};
 
     #[bench]
     fn benchmark_add() {
         for i in 0..1000 {
-            add(i, i + 1);
+            black_box(add(black_box(i), i + 1));
         }
     }
 
@@ -1,10 +1,14 @@
-use libtest_mimic::Arguments;
-use test_runner;
+#![allow(clippy::identity_op)]
 
+/// Entry point called by the C++ test driver.
+///
+/// # Safety
+///
+/// Call it once, from the `main` of the test driver.
 #[unsafe(no_mangle)]
 pub unsafe extern "C" fn some_tests_main() -> i32 {
     let tests = satchel::get_tests!();
-    let args = Arguments::from_args();
+    let args = test_runner::RunnerArgs::from_args();
 
     if test_runner::run_tests(tests, args) {
         0
@@ -13,22 +17,24 @@ pub unsafe extern "C" fn some_tests_main() -> i32 {
     }
 }
 
+test_runner::export_c_api!(somelib);
+
 pub fn add(left: u64, right: u64) -> u64 {
     left + right
 }
 
 mod tests {
     use super::*;
-    use satchel::{bench, test};
+    use satchel::{bench, black_box, test};
 
     #[bench]
     fn benchmark_add() {
         for i in 0..1000 {
-            add(i, i + 1);
+            black_box(add(black_box(i), i + 1));
         }
     }
 
-    #[test]
+    #[test(smoke)]
     fn it_works() {
         let result = add(2, 2);
         assert_eq!(result, 4);
@@ -40,12 +46,24 @@ mod tests {
         assert_eq!(result, 4);
     }
 
+    #[test]
+    #[ignore = "slow"]
+    fn it_adds_large_numbers() {
+        assert_eq!(add(u64::MAX - 1, 1), u64::MAX);
+    }
+
     #[test]
     fn it_handles_zero() {
         let result = 0 + 0;
         assert_eq!(result, 0);
     }
 
+    // Neither the unbalanced bracket nor the end of a CMake bracket argument may break discovery.
+    #[test(range = "[0, 10)", note = "]==]")]
+    fn it_adds_up_to_ten() {
+        assert_eq!(add(3, 7), 10);
+    }
+
     #[test]
     fn test_that_panics() {
         panic!("This test should panic");
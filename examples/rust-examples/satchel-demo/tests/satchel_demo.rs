@@ -1,9 +1,7 @@
-use libtest_mimic::Arguments;
-use test_runner;
-
 pub fn discover_and_run() -> bool {
+    test_runner::register_executor("counting", tests::CountingExecutor);
     let tests = satchel::get_tests!();
-    let args = Arguments::from_args();
+    let args = test_runner::RunnerArgs::from_args();
     test_runner::run_tests(tests, args)
 }
 
@@ -13,23 +11,19 @@ fn main() {
 }
 
 pub mod tests {
-    use satchel::{bench, test};
+    use satchel::{Bencher, Throughput, bench, fixture, test, test_case};
     use satchel_demo::multiply;
     use std::sync::Arc;
     use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
 
-    fn attributes_slice() -> &'static [&'static str] {
-        test_runner::current_case_attributes()
-    }
-
     fn threads_attribute_or(default: usize) -> usize {
-        attributes_slice()
-            .iter()
-            .find_map(|attr| attr.strip_prefix("--threads=")?.parse::<usize>().ok())
-            .unwrap_or(default)
+        satchel::CaseAttribute::find(test_runner::current_case_attributes(), "threads")
+            .and_then(|value| value.as_int())
+            .map_or(default, |threads| threads as usize)
     }
 
-    #[test]
+    // Select sets of tests with `--tags 'smoke & !slow'`
+    #[test(tags(smoke))]
     fn test_multiply_positive() {
         assert_eq!(multiply(2, 3), 6);
     }
@@ -78,6 +72,167 @@ pub mod tests {
         panic!("Error: invalid multiplier in calculation");
     }
 
+    // Tests may return Result and use `?`, like with the standard test macro
+    #[test]
+    fn test_result_with_question_mark() -> Result<(), std::num::ParseIntError> {
+        let factor: i32 = "7".parse()?;
+        assert_eq!(multiply(factor, 6), 42);
+        Ok(())
+    }
+
+    static COUNTING_EXECUTOR_RUNS: AtomicUsize = AtomicUsize::new(0);
+
+    // Delegates to the built-in executor while recording that it was selected.
+    pub struct CountingExecutor;
+
+    impl satchel::Executor for CountingExecutor {
+        fn block_on(&self, future: satchel::TestFuture) -> Result<(), satchel::TestError> {
+            COUNTING_EXECUTOR_RUNS.fetch_add(1, Ordering::SeqCst);
+            satchel::DefaultExecutor.block_on(future)
+        }
+    }
+
+    async fn multiply_later(left: i32, right: i32) -> i32 {
+        multiply(left, right)
+    }
+
+    // async fn tests run on the harness' default executor
+    #[test]
+    async fn test_async_multiply() {
+        assert_eq!(multiply_later(3, 4).await, 12);
+    }
+
+    // ... or on one selected by name
+    #[test(executor = "counting")]
+    async fn test_async_with_named_executor() -> Result<(), String> {
+        assert!(COUNTING_EXECUTOR_RUNS.load(Ordering::SeqCst) > 0);
+        match multiply_later(-1, 5).await {
+            -5 => Ok(()),
+            other => Err(format!("unexpected product {}", other)),
+        }
+    }
+
+    // One test per row: registered as multiply_table::case_1, multiply_table::negative_operand, ...
+    #[test_case(2, 3 => 6)]
+    #[test_case(-2, 3 => -6; label = "negative operand")]
+    #[test_case(0, 10 => 0; threads = 2)]
+    fn multiply_table(left: i32, right: i32) -> i32 {
+        multiply(left, right)
+    }
+
+    #[test_case(7, 6)]
+    #[test_case(3, 14; label = "reversed")]
+    fn test_multiply_commutes(left: i32, right: i32) -> Result<(), String> {
+        if multiply(left, right) == multiply(right, left) {
+            Ok(())
+        } else {
+            Err(format!("{} * {} is not commutative", left, right))
+        }
+    }
+
+    #[test]
+    fn test_case_rows_are_registered_individually() {
+        // The order of the linked cases is unspecified.
+        let mut names: Vec<_> = satchel::get_tests!()
+            .filter(|case| case.name.starts_with("multiply_table::"))
+            .map(|case| (case.name, case.attr_int("threads")))
+            .collect();
+        names.sort();
+        assert_eq!(
+            names,
+            [
+                ("multiply_table::case_1", None),
+                ("multiply_table::case_3", Some(2)),
+                ("multiply_table::negative_operand", None),
+            ]
+        );
+    }
+
+    // The runner fails the test as timed out if it runs longer than the limit
+    #[test(timeout = "10s")]
+    fn test_with_timeout() {
+        let case = satchel::get_tests!()
+            .find(|case| case.name == "test_with_timeout")
+            .expect("test is registered");
+        assert_eq!(case.timeout, Some(std::time::Duration::from_secs(10)));
+        assert_eq!(multiply(6, 7), 42);
+    }
+
+    // Set up once for the tests of this module and dropped after the last of them
+    #[fixture(scope = "module")]
+    fn shared_calls() -> AtomicUsize {
+        AtomicUsize::new(0)
+    }
+
+    #[test]
+    fn test_module_fixture_first_user(shared_calls: &AtomicUsize) {
+        shared_calls.fetch_add(1, Ordering::SeqCst);
+        assert!(shared_calls.load(Ordering::SeqCst) >= 1);
+    }
+
+    #[test]
+    fn test_module_fixture_second_user(shared_calls: &AtomicUsize) {
+        shared_calls.fetch_add(1, Ordering::SeqCst);
+        assert!(shared_calls.load(Ordering::SeqCst) >= 1);
+    }
+
+    pub struct Scratch {
+        pub values: Vec<i32>,
+    }
+
+    // Teardown is Drop, which runs even when the test panics
+    impl Drop for Scratch {
+        fn drop(&mut self) {
+            self.values.clear();
+        }
+    }
+
+    // Fixtures may fail to set up by returning an error
+    #[fixture]
+    fn scratch() -> Result<Scratch, String> {
+        Ok(Scratch {
+            values: vec![multiply(2, 3)],
+        })
+    }
+
+    #[test]
+    fn test_fixture_value_is_fresh(scratch: &Scratch) {
+        assert_eq!(scratch.values, [6]);
+    }
+
+    #[test]
+    #[should_panic(expected = "scratch is torn down anyway")]
+    fn test_fixture_torn_down_after_panic(scratch: &Scratch) {
+        assert_eq!(scratch.values.len(), 1);
+        panic!("scratch is torn down anyway");
+    }
+
+    #[test]
+    async fn test_async_with_fixtures(scratch: &Scratch, shared_calls: &AtomicUsize) {
+        assert_eq!(multiply_later(scratch.values[0], 7).await, 42);
+        shared_calls.fetch_add(1, Ordering::SeqCst);
+    }
+
+    #[test]
+    #[tag(smoke, "needs-gpu-stub")]
+    fn test_tags_are_registered() {
+        let tags_of = |name: &str| {
+            satchel::get_tests!()
+                .find(|case| case.name == name)
+                .map(|case| case.tags)
+        };
+        assert_eq!(
+            tags_of("test_tags_are_registered"),
+            Some(&["smoke", "needs-gpu-stub"][..])
+        );
+        assert_eq!(tags_of("test_multiply_positive"), Some(&["smoke"][..]));
+        assert_eq!(
+            tags_of("test_ignored_performance"),
+            Some(&["slow", "nightly"][..])
+        );
+        assert_eq!(tags_of("test_multiply_zero"), Some(&[][..]));
+    }
+
     // Test with ignore reason using #[ignore = "..."]
     #[test]
     #[ignore = "not yet implemented"]
@@ -100,8 +255,33 @@ pub mod tests {
         panic!("This panic is ignored");
     }
 
-    // Another test with ignore reason
+    fn multiply_is_broken() -> bool {
+        multiply(2, 2) != 4
+    }
+
+    // Conditional ignores are evaluated by the runner before any test runs
+    #[test]
+    #[ignore(unless_env = "SATCHEL_DEMO_DATABASE", reason = "needs a database")]
+    fn test_ignored_unless_env() {
+        assert!(std::env::var_os("SATCHEL_DEMO_DATABASE").is_some());
+    }
+
+    #[test]
+    #[ignore(if = multiply_is_broken)]
+    fn test_runs_unless_predicate_holds() {
+        assert_eq!(multiply(3, 3), 9);
+    }
+
+    // Reported as skipped rather than passed
     #[test]
+    fn test_skips_at_runtime() {
+        if std::env::var_os("SATCHEL_DEMO_DATABASE").is_none() {
+            satchel::skip!("SATCHEL_DEMO_DATABASE is not set");
+        }
+    }
+
+    // Another test with ignore reason
+    #[test(tags(slow, nightly))]
     #[ignore = "performance test - takes too long"]
     fn test_ignored_performance() {
         for i in 0..1000000 {
@@ -109,7 +289,7 @@ pub mod tests {
         }
     }
 
-    #[test("--threads=9")]
+    #[test(threads = 9)]
     fn test_attributes_control_parallelism() {
         let thread_count = threads_attribute_or(1);
         assert_eq!(thread_count, 9);
@@ -143,18 +323,84 @@ pub mod tests {
         }
     }
 
+    #[test(retries = 2)]
+    fn test_retries_until_it_passes() {
+        static ATTEMPTS: AtomicUsize = AtomicUsize::new(0);
+        let attempt = ATTEMPTS.fetch_add(1, Ordering::Relaxed) + 1;
+        assert!(attempt >= 3, "attempt {} fails", attempt);
+    }
+
+    // Typed key = value attributes sit next to flags and raw strings
+    #[test(mode = "smoke", ratio = 0.5, verbose = false, offset = -3, flaky, "--raw=1")]
+    fn test_typed_case_attributes() {
+        let case = satchel::get_tests!()
+            .find(|case| case.name == "test_typed_case_attributes")
+            .expect("test is registered");
+        assert_eq!(case.attr_str("mode"), Some("smoke"));
+        assert_eq!(case.attr_float("ratio"), Some(0.5));
+        assert_eq!(case.attr_bool("verbose"), Some(false));
+        assert_eq!(case.attr_int("offset"), Some(-3));
+        assert!(case.has_attr("flaky"));
+        assert!(case.has_attr("--raw=1"));
+        assert_eq!(case.attr_int("mode"), None);
+        let rendered: Vec<_> = case.case_attributes.iter().map(|a| a.to_string()).collect();
+        assert_eq!(
+            rendered,
+            [
+                "mode=smoke",
+                "ratio=0.5",
+                "verbose=false",
+                "offset=-3",
+                "flaky",
+                "--raw=1"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_source_location_points_at_function() {
+        let case = satchel::get_tests!()
+            .find(|case| case.name == "test_source_location_points_at_function")
+            .expect("test is registered");
+        assert!(case.location.file.ends_with("satchel_demo.rs"));
+        assert_eq!(case.location.line, line!() - 5);
+        assert_eq!(case.location.column, 8);
+    }
+
+    // black_box keeps the optimizer from deleting the otherwise unused products
     #[bench]
     fn bench_multiply() {
         for i in 0..500 {
-            let _ = multiply(i, i + 1);
+            satchel::black_box(multiply(satchel::black_box(i), i + 1));
         }
     }
 
+    // Only the routine is timed; building the input happens outside the measurement
+    #[bench]
+    fn bench_multiply_pairs(b: &mut Bencher) {
+        b.set_throughput(Throughput::Elements(256));
+        b.iter_batched(
+            || (0..256).map(|i| (i, 256 - i)).collect::<Vec<_>>(),
+            |pairs| pairs.iter().map(|&(l, r)| multiply(l, r)).sum::<i32>(),
+        );
+    }
+
+    #[bench]
+    fn bench_multiply_with_bencher(b: &mut Bencher) {
+        let operands: Vec<i32> = (0..100).collect();
+        b.iter(|| {
+            operands
+                .iter()
+                .map(|&i| multiply(satchel::black_box(i), i))
+                .sum::<i32>()
+        });
+    }
+
     #[bench]
     #[ignore]
     fn bench_multiply_ignored() {
         for i in 0..1000 {
-            let _ = multiply(i, i * 2);
+            satchel::black_box(multiply(i, i * 2));
         }
     }
 }
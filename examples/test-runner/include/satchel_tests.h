@@ -0,0 +1,65 @@
+/* Generated from examples/test-runner/src/c_api.rs; do not edit by hand. */
+#ifndef SATCHEL_TESTS_H
+#define SATCHEL_TESTS_H
+
+#include <stddef.h>
+#include <stdint.h>
+
+#ifdef __cplusplus
+#define SATCHEL_EXTERN_C extern "C"
+#else
+#define SATCHEL_EXTERN_C
+#endif
+
+/* SatchelTestInfo::kind */
+#define SATCHEL_KIND_UNIT 0
+#define SATCHEL_KIND_BENCH 1
+
+/* Return values of <prefix>_test_info, <prefix>_run_test and <prefix>_list_tests */
+#define SATCHEL_PASSED 0
+#define SATCHEL_FAILED 1
+#define SATCHEL_NOT_FOUND 2
+#define SATCHEL_INVALID_ARGUMENT 3
+#define SATCHEL_FIXTURE_ERROR 4
+#define SATCHEL_SKIPPED 5
+
+/* Modes of <prefix>_set_output */
+#define SATCHEL_OUTPUT_CAPTURE 0     /* show the output of failing cases (default) */
+#define SATCHEL_OUTPUT_SHOW 1        /* show the output of every case (--show-output) */
+#define SATCHEL_OUTPUT_NOCAPTURE 2   /* do not capture the output (--nocapture) */
+
+/* Strings are owned by the library and stay valid while it is loaded. */
+typedef struct SatchelTestInfo {
+    const char *name;          /* module_path::name, unique within the library */
+    const char *module_path;
+    const char *file;
+    uint32_t line;
+    uint32_t column;
+    int kind;                  /* SATCHEL_KIND_* */
+    int ignored;               /* nonzero if marked #[ignore] without a condition */
+    const char *ignore_reason; /* NULL if no reason was given */
+} SatchelTestInfo;
+
+/* Declares the functions exported by `test_runner::export_c_api!(prefix)`:
+ *   size_t <prefix>_test_count(void);
+ *   int <prefix>_test_info(size_t index, SatchelTestInfo *out);
+ *   int <prefix>_run_test(const char *name);
+ *   int <prefix>_list_tests(void);
+ *   int <prefix>_set_output(int mode);
+ *
+ * <prefix>_list_tests prints one line per case to stdout, for test discovery:
+ *   satchel-case<TAB>module_path::name<TAB>test|bench<TAB>0|1 (ignored)[<TAB>label]...
+ * where the labels are the tags of the case followed by its case attributes (key or key=value).
+ * Conditional ignores are listed as 0; <prefix>_run_test returns SATCHEL_SKIPPED if they hold.
+ *
+ * <prefix>_run_test redirects stdout and stderr, including output from C, while the case runs
+ * and writes what it captured after the case, as set by <prefix>_set_output (SATCHEL_OUTPUT_*).
+ */
+#define SATCHEL_DECLARE_TESTS(prefix)                                              \
+    SATCHEL_EXTERN_C size_t prefix##_test_count(void);                             \
+    SATCHEL_EXTERN_C int prefix##_test_info(size_t index, SatchelTestInfo *out);   \
+    SATCHEL_EXTERN_C int prefix##_run_test(const char *name);                      \
+    SATCHEL_EXTERN_C int prefix##_list_tests(void);                                \
+    SATCHEL_EXTERN_C int prefix##_set_output(int mode)
+
+#endif /* SATCHEL_TESTS_H */
@@ -0,0 +1,253 @@
+use crate::TagExpr;
+use crate::capture::OutputMode;
+use crate::{repeat, shuffle};
+use clap::Parser;
+use libtest_mimic::Arguments;
+use std::ffi::OsString;
+use std::path::PathBuf;
+
+/// Command line of the shared runner: the libtest-compatible flags understood by
+/// `libtest-mimic`, plus the runner's own options.
+#[derive(Parser, Debug, Clone, Default)]
+#[command(
+    help_template = "USAGE: [OPTIONS] [FILTER]\n\n{all-args}\n",
+    disable_version_flag = true,
+    mut_arg("nocapture", |arg| arg.help("Don't capture the output of the tests")),
+    mut_arg("show_output", |arg| arg.help("Show the captured output of passing tests too"))
+)]
+pub struct RunnerArgs {
+    #[command(flatten)]
+    pub libtest: Arguments,
+
+    /// Write a JUnit XML report to PATH. `{suite}` in PATH is replaced by the name of the crate
+    /// whose tests are run, so several crates linked into one host can share the option.
+    #[arg(long = "junit-xml", value_name = "PATH")]
+    pub junit_xml: Option<PathBuf>,
+
+    /// Run each test in a separate process, started from the current executable with the same
+    /// arguments, so a crash or `std::process::exit` fails only that test.
+    #[arg(long)]
+    pub isolate: bool,
+
+    /// Save the benchmark results under NAME in the baseline directory, merging with the results
+    /// already saved there.
+    #[arg(long = "save-baseline", value_name = "NAME")]
+    pub save_baseline: Option<String>,
+
+    /// Compare the benchmark results with the baseline NAME and fail if one got slower by more
+    /// than the regression threshold.
+    #[arg(long, value_name = "NAME")]
+    pub baseline: Option<String>,
+
+    /// Directory holding the baselines, one `NAME.json` file each.
+    #[arg(
+        long = "baseline-dir",
+        value_name = "DIR",
+        default_value = "satchel-baselines"
+    )]
+    pub baseline_dir: PathBuf,
+
+    /// Percent by which the median of a benchmark may exceed its baseline.
+    #[arg(
+        long = "regression-threshold",
+        value_name = "PERCENT",
+        default_value_t = 5.0
+    )]
+    pub regression_threshold: f64,
+
+    /// Run only the tests whose tags match EXPR, e.g. `smoke & !slow` or `(gpu | nightly)`.
+    /// Combines with the name FILTER.
+    #[arg(long, value_name = "EXPR")]
+    pub tags: Option<TagExpr>,
+
+    /// Run only shard I, counting from 0, of the `--shard-count` shards the tests are split
+    /// into.
+    #[arg(long = "shard-index", value_name = "I", requires = "shard_count")]
+    pub shard_index: Option<usize>,
+
+    /// Split the tests into N shards by a stable hash of their names.
+    #[arg(long = "shard-count", value_name = "N", requires = "shard_index")]
+    pub shard_count: Option<usize>,
+
+    /// Balance the shards by the test durations in PATH, written by `--save-timings` in an
+    /// earlier run, instead of hashing the names.
+    #[arg(long = "shard-timings", value_name = "PATH", requires = "shard_count")]
+    pub shard_timings: Option<PathBuf>,
+
+    /// Save the duration of each test that ran to PATH, merging with the durations already
+    /// saved there.
+    #[arg(long = "save-timings", value_name = "PATH")]
+    pub save_timings: Option<PathBuf>,
+
+    /// Give failing tests up to N more attempts, or as many as they request with
+    /// `#[test(retries = ...)]` if that is more. Tests that pass on a later attempt are
+    /// reported as flaky.
+    #[arg(long, value_name = "N", default_value_t = 0)]
+    pub retries: u32,
+
+    /// Run the tests in a random order. The seed is printed at startup.
+    #[arg(long)]
+    pub shuffle: bool,
+
+    /// Run the tests in the random order given by N, such as the seed printed by `--shuffle`.
+    #[arg(long = "shuffle-seed", value_name = "N")]
+    pub shuffle_seed: Option<u64>,
+
+    /// Instead of running the tests, find the test that makes the failing test NAME fail when
+    /// it runs before it. Pass the `--shuffle-seed` and filters of the failing run.
+    #[arg(long, value_name = "NAME", conflicts_with = "shuffle")]
+    pub bisect: Option<String>,
+
+    /// Instead of a normal run, run the tests N times, on `--test-threads` threads, and report
+    /// the first failure and the pass rate of each test.
+    #[arg(long, value_name = "N", conflicts_with = "bisect")]
+    pub repeat: Option<u64>,
+
+    /// Stop repeating at the first failure; without `--repeat`, repeat until a test fails.
+    #[arg(long = "until-fail", conflicts_with = "bisect")]
+    pub until_fail: bool,
+}
+
+impl RunnerArgs {
+    /// Number of tests to run at the same time, from `--test-threads` or else the available
+    /// parallelism.
+    pub(crate) fn test_threads(&self) -> usize {
+        self.libtest
+            .test_threads
+            .unwrap_or_else(|| std::thread::available_parallelism().map_or(1, |n| n.get()))
+    }
+
+    /// Whether every test runs in a child process of its own: with `--isolate`, and while the
+    /// output of tests running in parallel is captured. Redirecting captures the output of the
+    /// whole process, so tests running side by side are captured through the pipes of their
+    /// processes instead.
+    pub(crate) fn isolates(&self) -> bool {
+        self.isolate || (!self.libtest.nocapture && self.test_threads() > 1)
+    }
+
+    /// What happens to the output of the tests, from `--nocapture` and `--show-output`.
+    pub(crate) fn output_mode(&self) -> OutputMode {
+        if self.libtest.nocapture {
+            OutputMode::Passthrough
+        } else if self.libtest.show_output {
+            OutputMode::Show
+        } else {
+            OutputMode::Capture
+        }
+    }
+
+    /// The seed to shuffle the tests with, if they are shuffled.
+    pub(crate) fn shuffle_seed(&self) -> Option<u64> {
+        match self.shuffle_seed {
+            Some(seed) => Some(seed),
+            None if self.shuffle => Some(shuffle::random_seed()),
+            None => None,
+        }
+    }
+
+    /// How `--repeat` and `--until-fail` repeat the tests, if they do.
+    pub(crate) fn repeat_plan(&self) -> Option<repeat::Plan> {
+        (self.repeat.is_some() || self.until_fail).then(|| repeat::Plan {
+            iterations: self.repeat,
+            until_fail: self.until_fail,
+            threads: self.test_threads(),
+        })
+    }
+
+    /// Parses the arguments of the current process.
+    pub fn from_args() -> Self {
+        Parser::parse()
+    }
+
+    /// Parses the given arguments; the first item is the program name.
+    pub fn parse_from<I>(iter: I) -> Self
+    where
+        I: IntoIterator,
+        I::Item: Into<OsString> + Clone,
+    {
+        Parser::parse_from(iter)
+    }
+}
+
+impl From<Arguments> for RunnerArgs {
+    fn from(libtest: Arguments) -> Self {
+        RunnerArgs {
+            libtest,
+            ..RunnerArgs::default()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::RunnerArgs;
+    use crate::capture::OutputMode;
+    use clap::Parser;
+
+    #[test]
+    fn runner_options_and_libtest_flags_parse_together() {
+        let args = RunnerArgs::parse_from([
+            "host",
+            "--junit-xml",
+            "out/{suite}.xml",
+            "--isolate",
+            "--tags",
+            "smoke & !slow",
+            "--test-threads",
+            "1",
+            "it_works",
+        ]);
+        assert_eq!(
+            args.junit_xml.as_deref(),
+            Some(std::path::Path::new("out/{suite}.xml"))
+        );
+        assert!(args.isolate);
+        assert_eq!(
+            args.tags.map(|tags| tags.to_string()).as_deref(),
+            Some("(smoke & (!slow))")
+        );
+        assert_eq!(args.libtest.test_threads, Some(1));
+        assert_eq!(args.libtest.filter.as_deref(), Some("it_works"));
+    }
+
+    #[test]
+    fn shard_options_are_given_together() {
+        let args = RunnerArgs::parse_from(["host", "--shard-index", "1", "--shard-count", "4"]);
+        assert_eq!((args.shard_index, args.shard_count), (Some(1), Some(4)));
+        let error = RunnerArgs::try_parse_from(["host", "--shard-index", "1"]).unwrap_err();
+        assert_eq!(
+            error.kind(),
+            clap::error::ErrorKind::MissingRequiredArgument
+        );
+    }
+
+    #[test]
+    fn parallel_tests_are_captured_in_child_processes() {
+        let mode = |args: &[&str]| {
+            let args = RunnerArgs::parse_from(["host"].iter().chain(args));
+            (args.output_mode(), args.isolates())
+        };
+        assert_eq!(mode(&["--test-threads", "1"]), (OutputMode::Capture, false));
+        assert_eq!(mode(&["--test-threads", "4"]), (OutputMode::Capture, true));
+        assert_eq!(
+            mode(&["--test-threads", "1", "--isolate", "--show-output"]),
+            (OutputMode::Show, true)
+        );
+        assert_eq!(
+            mode(&["--test-threads", "4", "--nocapture"]),
+            (OutputMode::Passthrough, false)
+        );
+    }
+
+    #[test]
+    fn malformed_tag_expressions_are_usage_errors() {
+        let error = RunnerArgs::try_parse_from(["host", "--tags", "smoke &"]).unwrap_err();
+        assert_eq!(error.kind(), clap::error::ErrorKind::ValueValidation);
+    }
+
+    #[test]
+    fn verify_cli() {
+        use clap::CommandFactory;
+        RunnerArgs::command().debug_assert();
+    }
+}
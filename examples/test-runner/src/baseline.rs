@@ -0,0 +1,209 @@
+//! Benchmark baselines for `--save-baseline NAME` and `--baseline NAME`.
+//!
+//! A baseline is a JSON object in `<baseline dir>/<NAME>.json` that maps the
+//! `module_path::name` of each benchmark to its last measurement:
+//!
+//! ```json
+//! { "somelib::tests::benchmark_add": { "median_ns": 1200, "plus_minus_ns": 35 } }
+//! ```
+//!
+//! Saving merges into an existing file, so several libraries linked into one host can share a
+//! baseline.
+
+use crate::full_name;
+use crate::report::{CaseReport, Status};
+use libtest_mimic::Measurement;
+use serde_json::{Map, Value, json};
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// Path of the baseline called `name` in `dir`.
+pub(crate) fn path(dir: &Path, name: &str) -> PathBuf {
+    dir.join(format!("{}.json", name))
+}
+
+fn measurements(reports: &[CaseReport]) -> impl Iterator<Item = (String, Measurement)> + '_ {
+    reports.iter().filter_map(|report| match report.status {
+        Status::Measured(result) => Some((full_name(report.case), result.measurement)),
+        _ => None,
+    })
+}
+
+fn read(path: &Path) -> io::Result<Map<String, Value>> {
+    let text = std::fs::read_to_string(path)?;
+    match serde_json::from_str(&text)? {
+        Value::Object(entries) => Ok(entries),
+        _ => Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            "expected a JSON object",
+        )),
+    }
+}
+
+fn parse_entry(entry: &Value) -> Option<Measurement> {
+    Some(Measurement {
+        avg: entry.get("median_ns")?.as_u64()?,
+        variance: entry.get("plus_minus_ns")?.as_u64()?,
+    })
+}
+
+/// Merges the measurements in `reports` into the baseline at `path`.
+pub(crate) fn save(path: &Path, reports: &[CaseReport]) -> io::Result<()> {
+    let mut entries = match read(path) {
+        Ok(entries) => entries,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => Map::new(),
+        Err(e) => return Err(e),
+    };
+    for (name, measurement) in measurements(reports) {
+        entries.insert(
+            name,
+            json!({ "median_ns": measurement.avg, "plus_minus_ns": measurement.variance }),
+        );
+    }
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir)?;
+    }
+    let mut text = serde_json::to_string_pretty(&Value::Object(entries))?;
+    text.push('\n');
+    std::fs::write(path, text)
+}
+
+/// Change of the median relative to the baseline, in percent.
+fn percent_change(baseline: Measurement, current: Measurement) -> f64 {
+    if baseline.avg == 0 {
+        return 0.0;
+    }
+    (current.avg as f64 - baseline.avg as f64) / baseline.avg as f64 * 100.0
+}
+
+/// Compares the measurements in `reports` with the baseline at `path`, writing one line per
+/// benchmark to `out`. Returns whether no benchmark got slower by more than `threshold`
+/// percent.
+pub(crate) fn compare(
+    path: &Path,
+    reports: &[CaseReport],
+    threshold: f64,
+    out: &mut impl io::Write,
+) -> io::Result<bool> {
+    let entries = read(path)?;
+    let mut passed = true;
+    let mut lines = Vec::new();
+    for (name, current) in measurements(reports) {
+        let Some(baseline) = entries.get(&name).and_then(parse_entry) else {
+            lines.push((name, format!("{:>12} ns/iter  (new)", current.avg)));
+            continue;
+        };
+        let change = percent_change(baseline, current);
+        let verdict = if change > threshold {
+            passed = false;
+            "regressed"
+        } else if change < -threshold {
+            "improved"
+        } else {
+            "unchanged"
+        };
+        lines.push((
+            name,
+            format!(
+                "{:>12} ns/iter -> {:>12} ns/iter  {:+7.2}%  {}",
+                baseline.avg, current.avg, change, verdict
+            ),
+        ));
+    }
+    if lines.is_empty() {
+        return Ok(true);
+    }
+
+    writeln!(
+        out,
+        "\nbenchmarks compared with {} (regression threshold {}%):",
+        path.display(),
+        threshold
+    )?;
+    let width = lines.iter().map(|(name, _)| name.len()).max().unwrap_or(0);
+    for (name, line) in &lines {
+        writeln!(out, "  {:<width$}  {}", name, line, width = width)?;
+    }
+    Ok(passed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{compare, path, save};
+    use crate::bench::BenchResult;
+    use crate::report::{CaseReport, Status};
+    use crate::test_support::DEFAULT_CASE;
+    use libtest_mimic::Measurement;
+    use satchel::TestKind;
+    use satchel::test_harness::TestCase;
+    use std::time::Duration;
+
+    const BENCH: TestCase = TestCase {
+        module_path: "mycrate::benches",
+        kind: TestKind::Benchmark,
+        ..DEFAULT_CASE
+    };
+    static FAST: TestCase = TestCase {
+        name: "fast",
+        ..BENCH
+    };
+    static SLOW: TestCase = TestCase {
+        name: "slow",
+        ..BENCH
+    };
+    static ADDED: TestCase = TestCase {
+        name: "added",
+        ..BENCH
+    };
+
+    fn measured(case: &'static TestCase, avg: u64) -> CaseReport {
+        CaseReport {
+            case,
+            status: Status::Measured(BenchResult {
+                measurement: Measurement { avg, variance: 3 },
+                throughput: None,
+            }),
+            duration: Duration::from_secs(1),
+            message: None,
+            stdout: None,
+            stderr: None,
+            retried: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn saved_baselines_are_merged_and_compared() {
+        let dir = std::env::temp_dir().join(format!("satchel-baseline-{}", std::process::id()));
+        let path = path(&dir, "main");
+
+        save(&path, &[measured(&FAST, 100)]).unwrap();
+        save(&path, &[measured(&SLOW, 1000)]).unwrap();
+        let text = std::fs::read_to_string(&path).unwrap();
+        assert!(
+            text.contains("\"mycrate::benches::fast\": {\n    \"median_ns\": 100,"),
+            "{}",
+            text
+        );
+
+        let mut out = Vec::new();
+        let reports = [
+            measured(&FAST, 90),
+            measured(&SLOW, 1100),
+            measured(&ADDED, 5),
+        ];
+        let passed = compare(&path, &reports, 5.0, &mut out).unwrap();
+        let out = String::from_utf8(out).unwrap();
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        assert!(!passed);
+        assert!(out.contains("-10.00%  improved"), "{}", out);
+        assert!(out.contains("+10.00%  regressed"), "{}", out);
+        assert!(out.contains("(new)"), "{}", out);
+
+        let mut quiet = Vec::new();
+        assert!(
+            compare(&path, &[measured(&FAST, 1)], 5.0, &mut quiet).is_err(),
+            "a missing baseline is an error"
+        );
+    }
+}
@@ -0,0 +1,306 @@
+//! Benchmark measurement for `--bench`.
+//!
+//! A benchmark is first warmed up for [`WARM_UP`], which also estimates the time of one
+//! iteration. From that estimate the runner picks how many samples to take and how many
+//! iterations to time together in each sample, so the measurement takes about [`BUDGET`]
+//! whether an iteration takes nanoseconds or seconds. Each sample times a whole batch with one
+//! pair of clock reads, which keeps the clock's own cost and resolution out of short bodies.
+//!
+//! The result is the median time per iteration with a distribution-free 95% confidence interval
+//! from order statistics, so a few slow samples (a page fault, a context switch) neither shift
+//! it nor need to be discarded. Samples outside Tukey's fences are counted as outliers.
+//!
+//! Benchmarks taking a [`Bencher`](satchel::Bencher) are measured the same way through
+//! [`Driver`]; only their routine is timed, while the plan also accounts for their setup.
+
+use crate::full_name;
+use crate::report::{CaseReport, Status};
+use libtest_mimic::Measurement;
+use satchel::{BenchDriver, Throughput, Timer};
+use std::convert::Infallible;
+use std::io;
+use std::time::{Duration, Instant};
+
+/// Time spent running the benchmark before measuring.
+const WARM_UP: Duration = Duration::from_millis(300);
+/// Time the measurement aims for, after warm-up.
+const BUDGET: Duration = Duration::from_secs(1);
+/// Samples taken when iterations are short enough.
+const MAX_SAMPLES: u64 = 50;
+/// Samples taken even if that exceeds the budget, as fewer leave no usable interval.
+const MIN_SAMPLES: u64 = 5;
+/// Quantile of the standard normal distribution for a 95% confidence level.
+const Z_95: f64 = 1.96;
+
+/// Outcome of measuring a benchmark, in nanoseconds per iteration.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct Summary {
+    pub samples: usize,
+    /// Iterations timed together in each sample.
+    pub iterations: u64,
+    pub median: f64,
+    /// 95% confidence interval of the median.
+    pub confidence_interval: (f64, f64),
+    /// Samples beyond 1.5 but within 3 interquartile ranges of the quartiles.
+    pub mild_outliers: usize,
+    /// Samples beyond 3 interquartile ranges of the quartiles.
+    pub severe_outliers: usize,
+}
+
+impl Summary {
+    /// Converts to libtest-mimic's measurement, printed as `<median> ns/iter (+/- <half width
+    /// of the confidence interval>)`.
+    pub fn measurement(&self) -> libtest_mimic::Measurement {
+        let (low, high) = self.confidence_interval;
+        libtest_mimic::Measurement {
+            avg: self.median.round() as u64,
+            variance: ((high - low) / 2.0).round() as u64,
+        }
+    }
+
+    /// A note for the user if so many samples are outliers that the result is likely noisy.
+    pub fn noise_warning(&self) -> Option<String> {
+        let outliers = self.mild_outliers + self.severe_outliers;
+        (outliers * 10 > self.samples).then(|| {
+            format!(
+                "{} of {} samples are outliers ({} mild, {} severe); the measurement may be noisy",
+                outliers, self.samples, self.mild_outliers, self.severe_outliers
+            )
+        })
+    }
+}
+
+/// Measurement of a benchmark, with the throughput it declared.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) struct BenchResult {
+    pub measurement: Measurement,
+    pub throughput: Option<Throughput>,
+}
+
+impl BenchResult {
+    /// Throughput at the median time, e.g. `512.00 MB/s` or `2000000 elements/s`.
+    pub fn rate(&self) -> Option<String> {
+        let per_second = |amount: u64| amount as f64 * 1e9 / self.measurement.avg as f64;
+        match self.throughput? {
+            _ if self.measurement.avg == 0 => None,
+            Throughput::Bytes(bytes) => Some(format!("{:.2} MB/s", per_second(bytes) / 1e6)),
+            Throughput::Elements(elements) => {
+                Some(format!("{:.0} elements/s", per_second(elements)))
+            }
+        }
+    }
+}
+
+/// Warms up and measures a benchmark. `sample(iterations)` runs the body `iterations` times
+/// and returns the time to count for them.
+pub(crate) fn measure<E>(mut sample: impl FnMut(u64) -> Result<Duration, E>) -> Result<Summary, E> {
+    let nanos_per_iteration = warm_up(&mut sample)?;
+    let (sample_count, iterations) = plan(nanos_per_iteration, BUDGET);
+
+    let mut samples = Vec::with_capacity(sample_count as usize);
+    for _ in 0..sample_count {
+        samples.push(sample(iterations)?.as_nanos() as f64 / iterations as f64);
+    }
+    Ok(analyze(samples, iterations))
+}
+
+/// Sample of a benchmark measured as a whole, where `iteration` runs the body once.
+pub(crate) fn timed<E>(
+    mut iteration: impl FnMut() -> Result<(), E>,
+) -> impl FnMut(u64) -> Result<Duration, E> {
+    move |iterations| {
+        let start = Instant::now();
+        for _ in 0..iterations {
+            iteration()?;
+        }
+        Ok(start.elapsed())
+    }
+}
+
+struct InstantTimer(Instant);
+
+impl Timer for InstantTimer {
+    fn start(&mut self) {
+        self.0 = Instant::now();
+    }
+
+    fn elapsed(&self) -> Duration {
+        self.0.elapsed()
+    }
+}
+
+/// [`BenchDriver`] measuring the routines a benchmark hands to its
+/// [`Bencher`](satchel::Bencher). The last measured routine is the result.
+#[derive(Debug, Default)]
+pub(crate) struct Driver {
+    pub summary: Option<Summary>,
+}
+
+impl BenchDriver for Driver {
+    fn measure(&mut self, sample: &mut dyn FnMut(u64, &mut dyn Timer) -> Duration) {
+        let mut timer = InstantTimer(Instant::now());
+        let Ok(summary) = measure(|iterations| Ok::<_, Infallible>(sample(iterations, &mut timer)));
+        self.summary = Some(summary);
+    }
+}
+
+/// Writes the throughput of the benchmarks in `reports` that declared one.
+pub(crate) fn write_throughput(reports: &[CaseReport], out: &mut impl io::Write) -> io::Result<()> {
+    let rates: Vec<(String, String)> = reports
+        .iter()
+        .filter_map(|report| match &report.status {
+            Status::Measured(result) => Some((full_name(report.case), result.rate()?)),
+            _ => None,
+        })
+        .collect();
+    if rates.is_empty() {
+        return Ok(());
+    }
+    writeln!(out, "\nbenchmark throughput:")?;
+    let width = rates.iter().map(|(name, _)| name.len()).max().unwrap_or(0);
+    for (name, rate) in &rates {
+        writeln!(out, "  {:<width$}  {:>20}", name, rate, width = width)?;
+    }
+    Ok(())
+}
+
+/// Runs samples of doubling size until [`WARM_UP`] has passed and returns the average wall
+/// time of one iteration in nanoseconds. Wall time includes any setup outside the timed part of
+/// a sample, so the plan keeps benchmarks with expensive setup within the budget.
+fn warm_up<E>(sample: &mut impl FnMut(u64) -> Result<Duration, E>) -> Result<f64, E> {
+    let start = Instant::now();
+    let mut batch = 1u64;
+    let mut total = 0u64;
+    loop {
+        sample(batch)?;
+        total += batch;
+        if start.elapsed() >= WARM_UP {
+            return Ok(start.elapsed().as_nanos() as f64 / total as f64);
+        }
+        batch = batch.saturating_mul(2);
+    }
+}
+
+/// Chooses the number of samples and the iterations per sample for a body taking
+/// `nanos_per_iteration`, so that all samples together take about `budget`.
+fn plan(nanos_per_iteration: f64, budget: Duration) -> (u64, u64) {
+    let budget = budget.as_nanos() as f64;
+    let per_iteration = nanos_per_iteration.max(1.0);
+    let samples = ((budget / per_iteration) as u64).clamp(MIN_SAMPLES, MAX_SAMPLES);
+    let iterations = ((budget / samples as f64 / per_iteration) as u64).max(1);
+    (samples, iterations)
+}
+
+/// Linearly interpolated quantile `q` of `sorted`.
+fn quantile(sorted: &[f64], q: f64) -> f64 {
+    let position = q * (sorted.len() - 1) as f64;
+    let below = position.floor() as usize;
+    let above = position.ceil() as usize;
+    sorted[below] + (sorted[above] - sorted[below]) * (position - below as f64)
+}
+
+fn analyze(mut samples: Vec<f64>, iterations: u64) -> Summary {
+    samples.sort_by(f64::total_cmp);
+    let n = samples.len();
+    let median = quantile(&samples, 0.5);
+
+    // The ranks of the sorted samples enclosing the median with 95% confidence follow from the
+    // binomial distribution of samples below it, approximated by the normal distribution.
+    let spread = Z_95 * (n as f64).sqrt() / 2.0;
+    let low_rank = ((n as f64 / 2.0 - spread).floor() as usize).max(1);
+    let high_rank = ((n as f64 / 2.0 + 1.0 + spread).ceil() as usize).min(n);
+    let confidence_interval = (samples[low_rank - 1], samples[high_rank - 1]);
+
+    let (q1, q3) = (quantile(&samples, 0.25), quantile(&samples, 0.75));
+    let iqr = q3 - q1;
+    let beyond = |fence: f64| {
+        samples
+            .iter()
+            .filter(|&&sample| sample < q1 - fence * iqr || sample > q3 + fence * iqr)
+            .count()
+    };
+    let severe_outliers = beyond(3.0);
+
+    Summary {
+        samples: n,
+        iterations,
+        median,
+        confidence_interval,
+        mild_outliers: beyond(1.5) - severe_outliers,
+        severe_outliers,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{BenchResult, Summary, analyze, plan};
+    use libtest_mimic::Measurement;
+    use satchel::Throughput;
+    use std::time::Duration;
+
+    #[test]
+    fn iterations_are_batched_for_short_bodies_and_samples_capped_for_long_ones() {
+        let budget = Duration::from_secs(1);
+        // 10 ns per iteration: 50 samples of 2 million iterations.
+        assert_eq!(plan(10.0, budget), (50, 2_000_000));
+        // 100 ms per iteration: 10 samples of one iteration.
+        assert_eq!(plan(100e6, budget), (10, 1));
+        // 2 s per iteration: still enough samples for an interval.
+        assert_eq!(plan(2e9, budget), (5, 1));
+        // Bodies faster than the clock still get a finite plan.
+        assert_eq!(plan(0.0, budget), (50, 20_000_000));
+    }
+
+    #[test]
+    fn median_interval_and_outliers() {
+        let mut samples: Vec<f64> = (0..50).map(|i| 100.0 + f64::from(i % 10)).collect();
+        samples[7] = 500.0;
+        samples[31] = 118.0;
+        let summary = analyze(samples, 1000);
+        assert_eq!(summary.median, 105.0);
+        assert_eq!(summary.confidence_interval, (103.0, 106.0));
+        assert_eq!((summary.mild_outliers, summary.severe_outliers), (1, 1));
+        assert_eq!(summary.noise_warning(), None);
+
+        let measurement = summary.measurement();
+        assert_eq!((measurement.avg, measurement.variance), (105, 2));
+    }
+
+    #[test]
+    fn many_outliers_are_reported_as_noise() {
+        let summary = Summary {
+            samples: 50,
+            iterations: 1,
+            median: 10.0,
+            confidence_interval: (9.0, 11.0),
+            mild_outliers: 4,
+            severe_outliers: 2,
+        };
+        assert_eq!(
+            summary.noise_warning().as_deref(),
+            Some("6 of 50 samples are outliers (4 mild, 2 severe); the measurement may be noisy")
+        );
+    }
+
+    #[test]
+    fn declared_throughput_is_reported_as_a_rate() {
+        let result = |avg, throughput| BenchResult {
+            measurement: Measurement { avg, variance: 0 },
+            throughput,
+        };
+        assert_eq!(
+            result(1000, Some(Throughput::Bytes(4096)))
+                .rate()
+                .as_deref(),
+            Some("4096.00 MB/s")
+        );
+        assert_eq!(
+            result(250, Some(Throughput::Elements(1000)))
+                .rate()
+                .as_deref(),
+            Some("4000000000 elements/s")
+        );
+        assert_eq!(result(1000, None).rate(), None);
+        assert_eq!(result(0, Some(Throughput::Bytes(1))).rate(), None);
+    }
+}
@@ -0,0 +1,202 @@
+//! Bisection for `--bisect NAME`: finds the test that makes NAME fail when it runs earlier in the
+//! same process, for example by leaving global state behind.
+//!
+//! The tests that run before NAME in the order of the run (use the `--shuffle-seed` of the
+//! failing run) are the candidates. Each step starts the current executable again and runs one
+//! half of the candidates followed by NAME, in their original order; the half after which NAME
+//! still fails is kept. The child is started with the arguments of the parent and the
+//! environment variables below, like an `--isolate` child.
+
+use crate::fixtures::FixtureCache;
+use crate::{execute_case, full_name};
+use satchel::test_harness::TestCase;
+use std::io::{self, Write};
+use std::path::PathBuf;
+use std::process::{Command, Stdio};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+/// Full names of the cases the child runs, in order, one per line; the last one is checked.
+const SEQUENCE_ENV: &str = "SATCHEL_BISECT_SEQUENCE";
+/// File the child writes the outcome of the last case to.
+const RESULT_ENV: &str = "SATCHEL_BISECT_RESULT";
+
+/// Names of the cases to run if this process is a bisection child.
+pub(crate) fn requested_sequence() -> Option<Vec<String>> {
+    let names = std::env::var(SEQUENCE_ENV).ok()?;
+    Some(names.lines().map(str::to_string).collect())
+}
+
+/// Runs the cases called `names` in order and exits the process, if they are all among
+/// `cases`. Returns otherwise, as they may belong to another library linked into the same host.
+pub(crate) fn run_requested_sequence(cases: &[&'static TestCase], names: &[String]) {
+    let Some(sequence) = names
+        .iter()
+        .map(|name| cases.iter().copied().find(|case| full_name(case) == *name))
+        .collect::<Option<Vec<&'static TestCase>>>()
+    else {
+        return;
+    };
+    let fixtures = FixtureCache::default();
+    fixtures.schedule(&sequence);
+    let mut passed = false;
+    for case in &sequence {
+        passed = execute_case(case, true, &fixtures).is_ok();
+    }
+    fixtures.finish();
+    let outcome = if passed { "passed" } else { "failed" };
+    let written =
+        std::env::var_os(RESULT_ENV).is_some_and(|path| std::fs::write(path, outcome).is_ok());
+    std::process::exit(if written { 0 } else { 1 });
+}
+
+fn result_path() -> PathBuf {
+    static NEXT: AtomicUsize = AtomicUsize::new(0);
+    std::env::temp_dir().join(format!(
+        "satchel-{}-{}.bisect",
+        std::process::id(),
+        NEXT.fetch_add(1, Ordering::Relaxed)
+    ))
+}
+
+/// Runs `before` and then `target` in a child process and returns whether `target` failed.
+/// A child that ends without reporting, because a case crashed it, counts as a failure.
+fn fails_after(before: &[&'static TestCase], target: &'static TestCase) -> io::Result<bool> {
+    let path = result_path();
+    let names: Vec<String> = before
+        .iter()
+        .chain([&target])
+        .map(|case| full_name(case))
+        .collect();
+    Command::new(std::env::current_exe()?)
+        .args(std::env::args_os().skip(1))
+        .env(SEQUENCE_ENV, names.join("\n"))
+        .env(RESULT_ENV, &path)
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .status()?;
+    let outcome = std::fs::read_to_string(&path).ok();
+    let _ = std::fs::remove_file(&path);
+    Ok(outcome.as_deref() != Some("passed"))
+}
+
+/// Narrows `candidates`, in which `fails` holds, to the smallest half in which it still holds.
+/// Returns a single candidate if one alone makes the target fail, or the remaining candidates
+/// if the failure needs several of them together.
+fn narrow<T: Copy>(
+    mut candidates: Vec<T>,
+    mut fails: impl FnMut(&[T]) -> io::Result<bool>,
+) -> io::Result<Vec<T>> {
+    while candidates.len() > 1 {
+        let (first, second) = candidates.split_at(candidates.len() / 2);
+        if fails(first)? {
+            candidates = first.to_vec();
+        } else if fails(second)? {
+            candidates = second.to_vec();
+        } else {
+            break;
+        }
+    }
+    Ok(candidates)
+}
+
+/// Looks for the case among those before `target` in `order` that makes `target` fail, and
+/// writes the steps and the result to `out`. Returns whether a single culprit was found.
+pub(crate) fn bisect(order: &[&'static TestCase], target: &str, out: &mut impl Write) -> bool {
+    match search(order, target, out) {
+        Ok(found) => found,
+        Err(e) => {
+            eprintln!("error: failed to bisect {}: {}", target, e);
+            false
+        }
+    }
+}
+
+fn search(order: &[&'static TestCase], target: &str, out: &mut impl Write) -> io::Result<bool> {
+    let Some(position) = order.iter().position(|case| full_name(case) == target) else {
+        writeln!(out, "bisect: {} is not among the tests of this run", target)?;
+        return Ok(false);
+    };
+    let target_case = order[position];
+    let before = &order[..position];
+    let mut fails = |candidates: &[&'static TestCase]| {
+        let failed = fails_after(candidates, target_case)?;
+        writeln!(
+            out,
+            "bisect: {} after {} test{}: {}",
+            target,
+            candidates.len(),
+            if candidates.len() == 1 { "" } else { "s" },
+            if failed { "failed" } else { "passed" }
+        )?;
+        Ok(failed)
+    };
+
+    if fails(&[])? {
+        writeln!(
+            out,
+            "bisect: {} fails on its own; the tests before it are not the cause",
+            target
+        )?;
+        return Ok(false);
+    }
+    if !fails(before)? {
+        writeln!(
+            out,
+            "bisect: {} passes after the tests before it; pass the --shuffle-seed and filters \
+             of the failing run",
+            target
+        )?;
+        return Ok(false);
+    }
+    let culprits = narrow(before.to_vec(), &mut fails)?;
+    if let [culprit] = culprits[..] {
+        writeln!(
+            out,
+            "bisect: {} fails when {} runs before it",
+            target,
+            full_name(culprit)
+        )?;
+        return Ok(true);
+    }
+    writeln!(
+        out,
+        "bisect: {} fails only after several of these tests together:",
+        target
+    )?;
+    for culprit in culprits {
+        writeln!(out, "    {}", full_name(culprit))?;
+    }
+    Ok(false)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::narrow;
+
+    #[test]
+    fn a_single_culprit_is_found_by_halving() {
+        let mut steps = 0;
+        let culprits = narrow((0..20).collect(), |candidates: &[u32]| {
+            steps += 1;
+            Ok(candidates.contains(&13))
+        })
+        .unwrap();
+        assert_eq!(culprits, [13]);
+        assert!(steps <= 10, "{} steps", steps);
+    }
+
+    #[test]
+    fn culprits_needed_together_are_all_reported() {
+        let culprits = narrow((0..8).collect(), |candidates: &[u32]| {
+            Ok(candidates.contains(&1) && candidates.contains(&6))
+        })
+        .unwrap();
+        assert_eq!(culprits, [0, 1, 2, 3, 4, 5, 6, 7]);
+        let culprits = narrow((0..8).collect(), |candidates: &[u32]| {
+            Ok(candidates.contains(&4) && candidates.contains(&5))
+        })
+        .unwrap();
+        assert_eq!(culprits, [4, 5]);
+    }
+}
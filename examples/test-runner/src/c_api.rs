@@ -0,0 +1,653 @@
+//! C ABI for hosts that list and run tests themselves, one case at a time.
+//!
+//! [`export_c_api!`](crate::export_c_api) exports `<prefix>_test_count`, `<prefix>_test_info`,
+//! `<prefix>_run_test`, `<prefix>_list_tests` and `<prefix>_set_output` from a `cdylib`; [`header`] renders the matching
+//! C declarations, checked in as `include/satchel_tests.h`.
+
+use crate::capture::{self, OutputMode};
+use crate::fixtures::FixtureCache;
+use crate::retry;
+use crate::{CaseError, evaluate_ignore, execute_case, full_name};
+use libtest_mimic::Failed;
+use satchel::TestKind;
+use satchel::test_harness::TestCase;
+use std::ffi::{CStr, CString, c_char, c_int};
+use std::io::{self, Write};
+use std::sync::atomic::{AtomicI32, Ordering};
+
+/// [`SatchelTestInfo::kind`] of a `#[test]`.
+pub const KIND_UNIT: c_int = 0;
+/// [`SatchelTestInfo::kind`] of a `#[bench]`.
+pub const KIND_BENCH: c_int = 1;
+
+/// The case ran and passed.
+pub const PASSED: c_int = 0;
+/// The case ran and failed; the failure message was written to stderr.
+pub const FAILED: c_int = 1;
+/// No case has the requested name or index.
+pub const NOT_FOUND: c_int = 2;
+/// A pointer argument was null or the name was not valid UTF-8.
+pub const INVALID_ARGUMENT: c_int = 3;
+/// A fixture of the case failed to set up or tear down; the message was written to stderr.
+pub const FIXTURE_ERROR: c_int = 4;
+/// The case called `satchel::skip!`, or the condition of its `#[ignore(...)]` holds; the reason
+/// was written to stderr.
+pub const SKIPPED: c_int = 5;
+
+/// Output mode of `<prefix>_set_output`: capture the output of a case and write it to the
+/// original streams only if the case fails (the default).
+pub const OUTPUT_CAPTURE: c_int = 0;
+/// Output mode: capture the output of a case and write it after the case, whatever the outcome
+/// (`--show-output`).
+pub const OUTPUT_SHOW: c_int = 1;
+/// Output mode: do not capture the output (`--nocapture`).
+pub const OUTPUT_NOCAPTURE: c_int = 2;
+
+/// Description of a case, filled in by `<prefix>_test_info`.
+///
+/// The strings are owned by the library and stay valid for as long as it is loaded.
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+pub struct SatchelTestInfo {
+    /// `module_path::name`, unique within the library; pass it to `<prefix>_run_test`.
+    pub name: *const c_char,
+    pub module_path: *const c_char,
+    pub file: *const c_char,
+    pub line: u32,
+    pub column: u32,
+    /// [`KIND_UNIT`] or [`KIND_BENCH`].
+    pub kind: c_int,
+    /// Nonzero if the case is marked `#[ignore]` without a condition. Conditions such as
+    /// `#[ignore(if_env = "CI")]` are evaluated by `<prefix>_run_test` instead.
+    pub ignored: c_int,
+    /// Reason given to `#[ignore = "..."]` or `#[ignore(..., reason = "...")]`, or null.
+    pub ignore_reason: *const c_char,
+}
+
+struct CaseStrings {
+    name: CString,
+    module_path: CString,
+    file: CString,
+    ignore_reason: Option<CString>,
+}
+
+/// Whether `case` is ignored regardless of the environment it runs in.
+fn ignored_unconditionally(case: &TestCase) -> bool {
+    case.ignore
+        .as_ref()
+        .is_some_and(|ignore| ignore.condition.is_none())
+}
+
+fn c_string(text: &str) -> CString {
+    // Interior NULs cannot come from Rust identifiers or paths, only from a reason string.
+    CString::new(text.replace('\0', "\\0")).unwrap()
+}
+
+/// Cases of one library together with the C strings handed out for them.
+///
+/// Built once per library by [`export_c_api!`](crate::export_c_api).
+pub struct CApi {
+    cases: Vec<(&'static TestCase, CaseStrings)>,
+    output: AtomicI32,
+}
+
+impl CApi {
+    pub fn new(tests: impl Iterator<Item = &'static TestCase>) -> Self {
+        let cases = tests
+            .map(|case| {
+                let strings = CaseStrings {
+                    name: c_string(&full_name(case)),
+                    module_path: c_string(case.module_path),
+                    file: c_string(case.location.file),
+                    ignore_reason: case
+                        .ignore
+                        .as_ref()
+                        .and_then(|ignore| ignore.reason)
+                        .map(c_string),
+                };
+                (case, strings)
+            })
+            .collect();
+        CApi {
+            cases,
+            output: AtomicI32::new(OUTPUT_CAPTURE),
+        }
+    }
+
+    pub fn count(&self) -> usize {
+        self.cases.len()
+    }
+
+    /// Fills `*out` with the description of the case at `index`.
+    ///
+    /// # Safety
+    ///
+    /// `out` must be null or valid for writing a [`SatchelTestInfo`].
+    pub unsafe fn info(&self, index: usize, out: *mut SatchelTestInfo) -> c_int {
+        if out.is_null() {
+            return INVALID_ARGUMENT;
+        }
+        let Some((case, strings)) = self.cases.get(index) else {
+            return NOT_FOUND;
+        };
+        let info = SatchelTestInfo {
+            name: strings.name.as_ptr(),
+            module_path: strings.module_path.as_ptr(),
+            file: strings.file.as_ptr(),
+            line: case.location.line,
+            column: case.location.column,
+            kind: match case.kind {
+                TestKind::Unit => KIND_UNIT,
+                TestKind::Benchmark => KIND_BENCH,
+            },
+            ignored: c_int::from(ignored_unconditionally(case)),
+            ignore_reason: strings
+                .ignore_reason
+                .as_ref()
+                .map_or(std::ptr::null(), |reason| reason.as_ptr()),
+        };
+        unsafe { out.write(info) };
+        PASSED
+    }
+
+    /// Runs the case called `name` (`module_path::name`), even if it is marked `#[ignore]`.
+    /// Benchmarks are run once, as with `--test`. A case whose `#[ignore(...)]` condition holds
+    /// when this is called is not run and reported as [`SKIPPED`].
+    ///
+    /// # Safety
+    ///
+    /// `name` must be null or point to a NUL-terminated string.
+    pub unsafe fn run(&self, name: *const c_char) -> c_int {
+        if name.is_null() {
+            return INVALID_ARGUMENT;
+        }
+        let Ok(name) = unsafe { CStr::from_ptr(name) }.to_str() else {
+            return INVALID_ARGUMENT;
+        };
+        self.run_by_name(name)
+    }
+
+    /// Sets what [`CApi::run`] does with the output of the cases: one of [`OUTPUT_CAPTURE`],
+    /// [`OUTPUT_SHOW`] or [`OUTPUT_NOCAPTURE`].
+    pub fn set_output(&self, mode: c_int) -> c_int {
+        if !matches!(mode, OUTPUT_CAPTURE | OUTPUT_SHOW | OUTPUT_NOCAPTURE) {
+            return INVALID_ARGUMENT;
+        }
+        self.output.store(mode, Ordering::Relaxed);
+        PASSED
+    }
+
+    fn output_mode(&self) -> OutputMode {
+        match self.output.load(Ordering::Relaxed) {
+            OUTPUT_SHOW => OutputMode::Show,
+            OUTPUT_NOCAPTURE => OutputMode::Passthrough,
+            _ => OutputMode::Capture,
+        }
+    }
+
+    /// Writes one line per case for test discovery, e.g. by `satchel_discover_tests` in CMake:
+    ///
+    /// ```text
+    /// satchel-case<TAB>module_path::name<TAB>test|bench<TAB>0|1 (ignored)[<TAB>label]...
+    /// ```
+    ///
+    /// The labels are the tags of the case followed by its case attributes, rendered as `key`
+    /// or `key=value`.
+    ///
+    /// Lines without the `satchel-case` marker are not part of the list, so the host may print
+    /// other output around it.
+    pub fn write_list(&self, out: &mut impl Write) -> io::Result<()> {
+        // Fields are tab separated and cases newline separated; neither may appear inside one.
+        let field = |text: &str| text.replace(['\t', '\n', '\r'], " ");
+        for (case, strings) in &self.cases {
+            write!(
+                out,
+                "satchel-case\t{}\t{}\t{}",
+                field(&strings.name.to_string_lossy()),
+                match case.kind {
+                    TestKind::Unit => "test",
+                    TestKind::Benchmark => "bench",
+                },
+                u8::from(ignored_unconditionally(case)),
+            )?;
+            for tag in case.tags {
+                write!(out, "\t{}", tag)?;
+            }
+            for attribute in case.case_attributes {
+                write!(out, "\t{}", field(&attribute.to_string()))?;
+            }
+            writeln!(out)?;
+        }
+        out.flush()
+    }
+
+    /// Prints the list described in [`CApi::write_list`] to stdout.
+    pub fn list(&self) -> c_int {
+        match self.write_list(&mut io::stdout().lock()) {
+            Ok(()) => PASSED,
+            Err(e) => {
+                eprintln!("error: failed to list tests: {}", e);
+                FAILED
+            }
+        }
+    }
+
+    fn run_by_name(&self, name: &str) -> c_int {
+        let Some((case, _)) = self
+            .cases
+            .iter()
+            .find(|(_, strings)| strings.name.to_bytes() == name.as_bytes())
+        else {
+            return NOT_FOUND;
+        };
+        if !ignored_unconditionally(case)
+            && let Some(reason) = evaluate_ignore(case)
+        {
+            return skipped(name, reason);
+        }
+        let mode = self.output_mode();
+        let attempt = || {
+            // Every attempt runs a single case, so every fixture scope is set up for it alone.
+            let fixtures = FixtureCache::default();
+            let run = || {
+                let result = execute_case(case, true, &fixtures);
+                (result, fixtures.finish())
+            };
+            if mode.captures() {
+                let (outcome, output) = capture::capture(run);
+                (outcome, Some(output))
+            } else {
+                (run(), None)
+            }
+        };
+        let retries = retry::retries(case, 0);
+        let mut failed_attempts = 0;
+        let ((result, torn_down), output) = loop {
+            let ((result, torn_down), output) = attempt();
+            match result {
+                Err(CaseError::Failed(failed)) if failed_attempts < retries => {
+                    failed_attempts += 1;
+                    eprintln!("test {} failed attempt {}, retrying", name, failed_attempts);
+                    if let Some(message) = failed.message() {
+                        eprintln!("{}", message);
+                    }
+                }
+                Err(CaseError::Failed(failed)) if failed_attempts > 0 => {
+                    let message = failed.message().unwrap_or_default();
+                    let failed =
+                        Failed::from(retry::note_attempts(message, failed_attempts as usize + 1));
+                    break ((Err(CaseError::Failed(failed)), torn_down), output);
+                }
+                result => break ((result, torn_down), output),
+            }
+        };
+        if failed_attempts > 0 && result.is_ok() {
+            eprintln!(
+                "note: test {} passed on attempt {} (flaky)",
+                name,
+                failed_attempts + 1
+            );
+        }
+        // The output of a failure is part of its message; a teardown error was written to the
+        // captured output.
+        let replay = |show: bool| {
+            if show && let Some(output) = &output {
+                output.replay();
+            }
+        };
+        let result = result.map_err(|error| match (error, &output) {
+            (CaseError::Failed(failed), Some(output)) => {
+                CaseError::Failed(output.append_to(failed))
+            }
+            (CaseError::Fixture(failed), Some(output)) => {
+                CaseError::Fixture(output.append_to(failed))
+            }
+            (error, _) => error,
+        });
+        match result {
+            Ok(_) if torn_down => {
+                replay(mode == OutputMode::Show);
+                PASSED
+            }
+            Ok(_) => {
+                replay(true);
+                FIXTURE_ERROR
+            }
+            Err(CaseError::Failed(failed)) => {
+                eprintln!("test {} failed", name);
+                if let Some(message) = failed.message() {
+                    eprintln!("{}", message);
+                }
+                FAILED
+            }
+            Err(CaseError::Fixture(failed)) => {
+                eprintln!("test {} errored", name);
+                if let Some(message) = failed.message() {
+                    eprintln!("{}", message);
+                }
+                FIXTURE_ERROR
+            }
+            Err(CaseError::Skipped(reason)) => {
+                replay(mode == OutputMode::Show);
+                skipped(name, reason)
+            }
+        }
+    }
+}
+
+fn skipped(name: &str, reason: Option<String>) -> c_int {
+    match reason {
+        Some(reason) => eprintln!("test {} skipped: {}", name, reason),
+        None => eprintln!("test {} skipped", name),
+    }
+    SKIPPED
+}
+
+/// Exports the C API for the tests of the calling crate.
+///
+/// `export_c_api!(somelib)` defines the C symbols `somelib_test_count`, `somelib_test_info`,
+/// `somelib_run_test`, `somelib_list_tests` and `somelib_set_output`, declared in C by
+/// `SATCHEL_DECLARE_TESTS(somelib)` from `include/satchel_tests.h`. The prefix keeps several libraries linked into one host apart.
+#[macro_export]
+macro_rules! export_c_api {
+    ($prefix:ident) => {
+        const _: () = {
+            fn api() -> &'static $crate::c_api::CApi {
+                static API: ::std::sync::OnceLock<$crate::c_api::CApi> =
+                    ::std::sync::OnceLock::new();
+                API.get_or_init(|| $crate::c_api::CApi::new(::satchel::get_tests!()))
+            }
+
+            #[unsafe(export_name = concat!(stringify!($prefix), "_test_count"))]
+            extern "C" fn test_count() -> usize {
+                api().count()
+            }
+
+            #[unsafe(export_name = concat!(stringify!($prefix), "_test_info"))]
+            unsafe extern "C" fn test_info(
+                index: usize,
+                out: *mut $crate::c_api::SatchelTestInfo,
+            ) -> ::std::ffi::c_int {
+                unsafe { api().info(index, out) }
+            }
+
+            #[unsafe(export_name = concat!(stringify!($prefix), "_run_test"))]
+            unsafe extern "C" fn run_test(name: *const ::std::ffi::c_char) -> ::std::ffi::c_int {
+                unsafe { api().run(name) }
+            }
+
+            #[unsafe(export_name = concat!(stringify!($prefix), "_list_tests"))]
+            extern "C" fn list_tests() -> ::std::ffi::c_int {
+                api().list()
+            }
+
+            #[unsafe(export_name = concat!(stringify!($prefix), "_set_output"))]
+            extern "C" fn set_output(mode: ::std::ffi::c_int) -> ::std::ffi::c_int {
+                api().set_output(mode)
+            }
+        };
+    };
+}
+
+/// Renders `include/satchel_tests.h`, the C declarations of this module.
+pub fn header() -> String {
+    format!(
+        r#"/* Generated from examples/test-runner/src/c_api.rs; do not edit by hand. */
+#ifndef SATCHEL_TESTS_H
+#define SATCHEL_TESTS_H
+
+#include <stddef.h>
+#include <stdint.h>
+
+#ifdef __cplusplus
+#define SATCHEL_EXTERN_C extern "C"
+#else
+#define SATCHEL_EXTERN_C
+#endif
+
+/* SatchelTestInfo::kind */
+#define SATCHEL_KIND_UNIT {KIND_UNIT}
+#define SATCHEL_KIND_BENCH {KIND_BENCH}
+
+/* Return values of <prefix>_test_info, <prefix>_run_test and <prefix>_list_tests */
+#define SATCHEL_PASSED {PASSED}
+#define SATCHEL_FAILED {FAILED}
+#define SATCHEL_NOT_FOUND {NOT_FOUND}
+#define SATCHEL_INVALID_ARGUMENT {INVALID_ARGUMENT}
+#define SATCHEL_FIXTURE_ERROR {FIXTURE_ERROR}
+#define SATCHEL_SKIPPED {SKIPPED}
+
+/* Modes of <prefix>_set_output */
+#define SATCHEL_OUTPUT_CAPTURE {OUTPUT_CAPTURE}     /* show the output of failing cases (default) */
+#define SATCHEL_OUTPUT_SHOW {OUTPUT_SHOW}        /* show the output of every case (--show-output) */
+#define SATCHEL_OUTPUT_NOCAPTURE {OUTPUT_NOCAPTURE}   /* do not capture the output (--nocapture) */
+
+/* Strings are owned by the library and stay valid while it is loaded. */
+typedef struct SatchelTestInfo {{
+    const char *name;          /* module_path::name, unique within the library */
+    const char *module_path;
+    const char *file;
+    uint32_t line;
+    uint32_t column;
+    int kind;                  /* SATCHEL_KIND_* */
+    int ignored;               /* nonzero if marked #[ignore] without a condition */
+    const char *ignore_reason; /* NULL if no reason was given */
+}} SatchelTestInfo;
+
+/* Declares the functions exported by `test_runner::export_c_api!(prefix)`:
+ *   size_t <prefix>_test_count(void);
+ *   int <prefix>_test_info(size_t index, SatchelTestInfo *out);
+ *   int <prefix>_run_test(const char *name);
+ *   int <prefix>_list_tests(void);
+ *   int <prefix>_set_output(int mode);
+ *
+ * <prefix>_list_tests prints one line per case to stdout, for test discovery:
+ *   satchel-case<TAB>module_path::name<TAB>test|bench<TAB>0|1 (ignored)[<TAB>label]...
+ * where the labels are the tags of the case followed by its case attributes (key or key=value).
+ * Conditional ignores are listed as 0; <prefix>_run_test returns SATCHEL_SKIPPED if they hold.
+ *
+ * <prefix>_run_test redirects stdout and stderr, including output from C, while the case runs
+ * and writes what it captured after the case, as set by <prefix>_set_output (SATCHEL_OUTPUT_*).
+ */
+#define SATCHEL_DECLARE_TESTS(prefix)                                              \
+    SATCHEL_EXTERN_C size_t prefix##_test_count(void);                             \
+    SATCHEL_EXTERN_C int prefix##_test_info(size_t index, SatchelTestInfo *out);   \
+    SATCHEL_EXTERN_C int prefix##_run_test(const char *name);                      \
+    SATCHEL_EXTERN_C int prefix##_list_tests(void);                                \
+    SATCHEL_EXTERN_C int prefix##_set_output(int mode)
+
+#endif /* SATCHEL_TESTS_H */
+"#
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        CApi, FAILED, INVALID_ARGUMENT, KIND_UNIT, NOT_FOUND, OUTPUT_NOCAPTURE, PASSED, SKIPPED,
+        SatchelTestInfo,
+    };
+    use crate::capture::OutputMode;
+    use crate::test_support::DEFAULT_CASE;
+    use satchel::test_harness::{Ignore, IgnoreCondition, SourceLocation, TestCase};
+    use satchel::{AttributeValue, CaseAttribute, EntryPoint};
+    use std::ffi::CStr;
+    use std::mem::MaybeUninit;
+
+    fn passes() {}
+
+    fn fails() {
+        panic!("boom");
+    }
+
+    fn skips() {
+        satchel::skip!("no {}", "device");
+    }
+
+    static CASES: [TestCase; 2] = [
+        TestCase {
+            name: "passes",
+            location: SourceLocation {
+                file: "src/lib.rs",
+                line: 3,
+                column: 4,
+            },
+            test_fn: EntryPoint::Plain(passes),
+            ignore: Some(Ignore {
+                reason: Some("slow"),
+                condition: None,
+            }),
+            ..DEFAULT_CASE
+        },
+        TestCase {
+            name: "fails",
+            test_fn: EntryPoint::Plain(fails),
+            ..DEFAULT_CASE
+        },
+    ];
+
+    #[test]
+    fn info_describes_each_case() {
+        let api = CApi::new(CASES.iter());
+        assert_eq!(api.count(), 2);
+
+        let mut info = MaybeUninit::<SatchelTestInfo>::uninit();
+        assert_eq!(unsafe { api.info(0, info.as_mut_ptr()) }, PASSED);
+        let info = unsafe { info.assume_init() };
+        let text = |ptr| unsafe { CStr::from_ptr(ptr) }.to_str().unwrap();
+        assert_eq!(text(info.name), "mycrate::tests::passes");
+        assert_eq!(text(info.module_path), "mycrate::tests");
+        assert_eq!(text(info.file), "src/lib.rs");
+        assert_eq!((info.line, info.column), (3, 4));
+        assert_eq!(info.kind, KIND_UNIT);
+        assert_eq!(info.ignored, 1);
+        assert_eq!(text(info.ignore_reason), "slow");
+
+        let mut info = MaybeUninit::<SatchelTestInfo>::uninit();
+        assert_eq!(unsafe { api.info(2, info.as_mut_ptr()) }, NOT_FOUND);
+        assert_eq!(
+            unsafe { api.info(0, std::ptr::null_mut()) },
+            INVALID_ARGUMENT
+        );
+    }
+
+    #[test]
+    fn run_reports_the_outcome_by_name() {
+        let api = CApi::new(CASES.iter());
+        assert_eq!(
+            unsafe { api.run(c"mycrate::tests::passes".as_ptr()) },
+            PASSED
+        );
+        assert_eq!(
+            unsafe { api.run(c"mycrate::tests::fails".as_ptr()) },
+            FAILED
+        );
+        assert_eq!(unsafe { api.run(c"fails".as_ptr()) }, NOT_FOUND);
+        assert_eq!(unsafe { api.run(std::ptr::null()) }, INVALID_ARGUMENT);
+    }
+
+    #[test]
+    fn skips_and_ignore_conditions_are_evaluated_when_run() {
+        const fn ignore_if(predicate: fn() -> bool) -> Option<Ignore> {
+            Some(Ignore {
+                reason: None,
+                condition: Some(IgnoreCondition::If {
+                    name: "predicate",
+                    predicate,
+                }),
+            })
+        }
+        static SKIPPING: [TestCase; 3] = [
+            TestCase {
+                name: "skips",
+                test_fn: EntryPoint::Plain(skips),
+                ..DEFAULT_CASE
+            },
+            TestCase {
+                name: "holds",
+                ignore: ignore_if(|| true),
+                ..DEFAULT_CASE
+            },
+            TestCase {
+                name: "does_not_hold",
+                test_fn: EntryPoint::Plain(fails),
+                ignore: ignore_if(|| false),
+                ..DEFAULT_CASE
+            },
+        ];
+        let api = CApi::new(SKIPPING.iter());
+
+        let mut info = MaybeUninit::<SatchelTestInfo>::uninit();
+        assert_eq!(unsafe { api.info(1, info.as_mut_ptr()) }, PASSED);
+        assert_eq!(unsafe { info.assume_init() }.ignored, 0);
+
+        assert_eq!(
+            unsafe { api.run(c"mycrate::tests::skips".as_ptr()) },
+            SKIPPED
+        );
+        assert_eq!(
+            unsafe { api.run(c"mycrate::tests::holds".as_ptr()) },
+            SKIPPED
+        );
+        assert_eq!(
+            unsafe { api.run(c"mycrate::tests::does_not_hold".as_ptr()) },
+            FAILED
+        );
+    }
+
+    #[test]
+    fn output_modes_are_validated() {
+        let api = CApi::new(CASES.iter());
+        assert_eq!(api.output_mode(), OutputMode::Capture);
+        assert_eq!(api.set_output(OUTPUT_NOCAPTURE), PASSED);
+        assert_eq!(api.set_output(7), INVALID_ARGUMENT);
+        assert_eq!(api.output_mode(), OutputMode::Passthrough);
+    }
+
+    #[test]
+    fn list_has_one_line_per_case() {
+        static ATTRIBUTED: TestCase = TestCase {
+            name: "attributed",
+            case_attributes: &[
+                CaseAttribute {
+                    key: "retry_on_failure",
+                    value: AttributeValue::Flag,
+                },
+                CaseAttribute {
+                    key: "mode",
+                    value: AttributeValue::Str("smoke\tfast"),
+                },
+                CaseAttribute {
+                    key: "threads",
+                    value: AttributeValue::Int(4),
+                },
+            ],
+            tags: &["smoke", "needs-gpu-stub"],
+            ..DEFAULT_CASE
+        };
+        let api = CApi::new(CASES.iter().chain([&ATTRIBUTED]));
+        let mut out = Vec::new();
+        api.write_list(&mut out).unwrap();
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            "satchel-case\tmycrate::tests::passes\ttest\t1\n\
+             satchel-case\tmycrate::tests::fails\ttest\t0\n\
+             satchel-case\tmycrate::tests::attributed\ttest\t0\tsmoke\tneeds-gpu-stub\tretry_on_failure\tmode=smoke fast\tthreads=4\n"
+        );
+    }
+
+    /// Set `SATCHEL_UPDATE_HEADER=1` to regenerate the checked-in header.
+    #[test]
+    fn checked_in_header_is_up_to_date() {
+        let path = concat!(env!("CARGO_MANIFEST_DIR"), "/include/satchel_tests.h");
+        if std::env::var_os("SATCHEL_UPDATE_HEADER").is_some() {
+            std::fs::write(path, super::header()).unwrap();
+        }
+        let checked_in = std::fs::read_to_string(path).unwrap_or_default();
+        assert!(
+            checked_in == super::header(),
+            "{} is out of date; rerun with SATCHEL_UPDATE_HEADER=1",
+            path
+        );
+    }
+}
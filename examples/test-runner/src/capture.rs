@@ -0,0 +1,236 @@
+//! Output capture: while a case runs, file descriptors 1 and 2 are redirected to temporary
+//! files, so `println!`, `eprintln!`, the panic message and whatever C code writes with `printf`
+//! end up with the case instead of in the middle of the runner's output.
+//!
+//! The redirection applies to the whole process, so it is only used while tests run one at a
+//! time, with `--test-threads 1` or on a single core. Tests running in parallel each run in a
+//! child process instead, like with `--isolate`, and are captured through its pipes. On
+//! platforms other than Unix the output is not captured.
+
+use crate::full_name;
+use crate::report::{CaseReport, Status};
+use libtest_mimic::Failed;
+use std::io::{self, Write};
+use std::sync::Mutex;
+
+/// What happens to the output of a case.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub(crate) enum OutputMode {
+    /// Capture the output and show it only if the case fails.
+    #[default]
+    Capture,
+    /// Capture the output and show it for passing cases too (`--show-output`).
+    Show,
+    /// Let the output through as it is written (`--nocapture`).
+    Passthrough,
+}
+
+impl OutputMode {
+    pub(crate) fn captures(self) -> bool {
+        self != OutputMode::Passthrough
+    }
+}
+
+/// Output captured from a case.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub(crate) struct Output {
+    pub stdout: String,
+    pub stderr: String,
+}
+
+impl Output {
+    /// The non-empty streams with their names.
+    fn streams(&self) -> impl Iterator<Item = (&'static str, &str)> {
+        [("stdout", self.stdout.as_str()), ("stderr", &self.stderr)]
+            .into_iter()
+            .filter(|(_, text)| !text.is_empty())
+    }
+
+    /// Appends the output to the message of `failed`, one `---- stream ----` section per
+    /// stream that has any.
+    pub fn append_to(&self, failed: Failed) -> Failed {
+        let mut message = failed.message().unwrap_or_default().to_string();
+        for (stream, text) in self.streams() {
+            message.push_str(&format!("\n---- {} ----\n{}", stream, text.trim_end()));
+        }
+        Failed::from(message)
+    }
+
+    /// Writes the output of a passing case back to the streams it came from.
+    pub fn replay(&self) {
+        let _ = io::stdout().write_all(self.stdout.as_bytes());
+        let _ = io::stderr().write_all(self.stderr.as_bytes());
+    }
+}
+
+/// Writes the captured output of the passing cases in `reports` for `--show-output`, in the
+/// `successes:` section of libtest.
+pub(crate) fn write_successes(reports: &[CaseReport], out: &mut impl Write) -> io::Result<()> {
+    let passed: Vec<&CaseReport> = reports
+        .iter()
+        .filter(|report| matches!(report.status, Status::Passed | Status::Measured(_)))
+        .filter(|report| report.stdout.is_some() || report.stderr.is_some())
+        .collect();
+    if passed.is_empty() {
+        return Ok(());
+    }
+    writeln!(out, "\nsuccesses:\n")?;
+    for report in &passed {
+        let name = full_name(report.case);
+        for (stream, text) in [("stdout", &report.stdout), ("stderr", &report.stderr)] {
+            if let Some(text) = text {
+                writeln!(out, "---- {} {} ----\n{}", name, stream, text.trim_end())?;
+            }
+        }
+    }
+    writeln!(out, "\nsuccesses:")?;
+    for report in &passed {
+        writeln!(out, "    {}", full_name(report.case))?;
+    }
+    Ok(())
+}
+
+/// Serializes captures, which redirect the descriptors of the whole process.
+static CAPTURE: Mutex<()> = Mutex::new(());
+
+/// Runs `f` with stdout and stderr redirected and returns its result with the output. If the
+/// streams cannot be redirected, `f` runs with them as they are and the output is empty.
+pub(crate) fn capture<T>(f: impl FnOnce() -> T) -> (T, Output) {
+    let _lock = CAPTURE
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner());
+    match imp::Redirect::start() {
+        Ok(redirect) => {
+            let value = f();
+            (value, redirect.finish())
+        }
+        Err(_) => (f(), Output::default()),
+    }
+}
+
+#[cfg(unix)]
+mod imp {
+    use super::Output;
+    use std::fs::File;
+    use std::io::{self, Read, Seek, Write};
+    use std::os::fd::{AsRawFd, FromRawFd, OwnedFd};
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    /// A temporary file, already unlinked, that a descriptor is redirected to.
+    struct Target {
+        fd: libc::c_int,
+        saved: OwnedFd,
+        file: File,
+    }
+
+    impl Target {
+        fn redirect(fd: libc::c_int) -> io::Result<Self> {
+            static NEXT: AtomicUsize = AtomicUsize::new(0);
+            let path = std::env::temp_dir().join(format!(
+                "satchel-{}-{}.out",
+                std::process::id(),
+                NEXT.fetch_add(1, Ordering::Relaxed)
+            ));
+            let file = File::options()
+                .read(true)
+                .write(true)
+                .create_new(true)
+                .open(&path)?;
+            std::fs::remove_file(&path)?;
+            let saved = unsafe { libc::dup(fd) };
+            if saved < 0 {
+                return Err(io::Error::last_os_error());
+            }
+            let saved = unsafe { OwnedFd::from_raw_fd(saved) };
+            if unsafe { libc::dup2(file.as_raw_fd(), fd) } < 0 {
+                return Err(io::Error::last_os_error());
+            }
+            Ok(Target { fd, saved, file })
+        }
+
+        fn restore(&self) {
+            unsafe { libc::dup2(self.saved.as_raw_fd(), self.fd) };
+        }
+
+        fn read(mut self) -> String {
+            let mut bytes = Vec::new();
+            let _ = self.file.rewind();
+            let _ = self.file.read_to_end(&mut bytes);
+            String::from_utf8_lossy(&bytes).into_owned()
+        }
+    }
+
+    /// Flushes what Rust and C buffered for the current destination of the descriptors.
+    fn flush() {
+        let _ = io::stdout().flush();
+        unsafe { libc::fflush(std::ptr::null_mut()) };
+    }
+
+    pub(super) struct Redirect {
+        stdout: Target,
+        stderr: Target,
+    }
+
+    impl Redirect {
+        pub fn start() -> io::Result<Self> {
+            flush();
+            let stdout = Target::redirect(libc::STDOUT_FILENO)?;
+            match Target::redirect(libc::STDERR_FILENO) {
+                Ok(stderr) => Ok(Redirect { stdout, stderr }),
+                Err(e) => {
+                    stdout.restore();
+                    Err(e)
+                }
+            }
+        }
+
+        pub fn finish(self) -> Output {
+            flush();
+            self.stdout.restore();
+            self.stderr.restore();
+            Output {
+                stdout: self.stdout.read(),
+                stderr: self.stderr.read(),
+            }
+        }
+    }
+}
+
+#[cfg(not(unix))]
+mod imp {
+    use super::Output;
+    use std::io;
+
+    pub(super) struct Redirect;
+
+    impl Redirect {
+        pub fn start() -> io::Result<Self> {
+            Err(io::Error::from(io::ErrorKind::Unsupported))
+        }
+
+        pub fn finish(self) -> Output {
+            Output::default()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Output;
+    use libtest_mimic::Failed;
+
+    #[test]
+    fn output_is_appended_to_failures_by_stream() {
+        let output = Output {
+            stdout: "connecting\nretrying\n".to_string(),
+            stderr: String::new(),
+        };
+        let failed = output.append_to(Failed::from("Unexpected panic: boom"));
+        assert_eq!(
+            failed.message(),
+            Some("Unexpected panic: boom\n---- stdout ----\nconnecting\nretrying")
+        );
+        let failed = Output::default().append_to(Failed::from("boom"));
+        assert_eq!(failed.message(), Some("boom"));
+    }
+}
@@ -0,0 +1,273 @@
+//! Dependencies between tests, from `#[test(depends_on = [...])]`.
+//!
+//! Every dependency must name a test of the run's crate, and dependencies must not form a cycle;
+//! both are checked at startup. Cases are dispatched after the cases they depend on, and a case
+//! waits for them to finish before it starts. If one of them failed or was skipped, the case is
+//! skipped with the reason, and so are the cases depending on it in turn. Dependencies that do
+//! not run, because they were filtered out or ignored, are not waited for; sharding keeps cases
+//! connected by dependencies together.
+
+use crate::full_name;
+use satchel::test_harness::TestCase;
+use std::collections::{HashMap, HashSet};
+use std::sync::{Condvar, Mutex, OnceLock};
+
+/// How a case that other cases may depend on ended.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Outcome {
+    Passed,
+    Failed,
+    Skipped,
+}
+
+/// For every case in `cases`, the positions of the cases among them it depends on. Dependencies
+/// on other cases are left out.
+pub(crate) fn edges(cases: &[&'static TestCase]) -> Vec<Vec<usize>> {
+    let positions: HashMap<String, usize> = cases
+        .iter()
+        .enumerate()
+        .map(|(position, case)| (full_name(case), position))
+        .collect();
+    cases
+        .iter()
+        .map(|case| {
+            case.dependencies()
+                .filter_map(|name| positions.get(&name).copied())
+                .collect()
+        })
+        .collect()
+}
+
+/// Checks that the dependencies of `cases`, all the registered cases of the run, name cases among
+/// them and do not form a cycle.
+pub(crate) fn check(cases: &[&'static TestCase]) -> Result<(), String> {
+    let names: HashSet<String> = cases.iter().map(|case| full_name(case)).collect();
+    for case in cases {
+        if let Some(unknown) = case.dependencies().find(|name| !names.contains(name)) {
+            return Err(format!(
+                "{} depends on `{}`, which is not a registered test",
+                full_name(case),
+                unknown
+            ));
+        }
+    }
+
+    // Depth-first search, keeping the path from the case it started at.
+    fn visit(
+        position: usize,
+        edges: &[Vec<usize>],
+        done: &mut [bool],
+        path: &mut Vec<usize>,
+    ) -> Option<Vec<usize>> {
+        if let Some(start) = path.iter().position(|&on_path| on_path == position) {
+            return Some([&path[start..], &[position]].concat());
+        }
+        if done[position] {
+            return None;
+        }
+        path.push(position);
+        for &dependency in &edges[position] {
+            if let Some(cycle) = visit(dependency, edges, done, path) {
+                return Some(cycle);
+            }
+        }
+        path.pop();
+        done[position] = true;
+        None
+    }
+
+    let edges = edges(cases);
+    let mut done = vec![false; cases.len()];
+    for position in 0..cases.len() {
+        if let Some(cycle) = visit(position, &edges, &mut done, &mut Vec::new()) {
+            let names: Vec<String> = cycle.iter().map(|&at| full_name(cases[at])).collect();
+            return Err(format!(
+                "tests depend on each other in a cycle: {}",
+                names.join(" -> ")
+            ));
+        }
+    }
+    Ok(())
+}
+
+/// Moves every case of `cases` behind the cases it depends on, keeping the order otherwise: a
+/// dependency that came later moves up to just before the first case that needs it. `cases` must
+/// have passed [`check`].
+pub(crate) fn order(cases: &mut Vec<&'static TestCase>) {
+    fn place(position: usize, edges: &[Vec<usize>], placed: &mut [bool], order: &mut Vec<usize>) {
+        if placed[position] {
+            return;
+        }
+        placed[position] = true;
+        for &dependency in &edges[position] {
+            place(dependency, edges, placed, order);
+        }
+        order.push(position);
+    }
+
+    let edges = edges(cases);
+    let mut placed = vec![false; cases.len()];
+    let mut order = Vec::with_capacity(cases.len());
+    for position in 0..cases.len() {
+        place(position, &edges, &mut placed, &mut order);
+    }
+    *cases = order.into_iter().map(|position| cases[position]).collect();
+}
+
+/// Outcomes of the cases of a run, for the cases depending on them.
+#[derive(Debug, Default)]
+pub(crate) struct Dependencies {
+    scheduled: OnceLock<HashSet<String>>,
+    outcomes: Mutex<HashMap<String, Outcome>>,
+    finished: Condvar,
+}
+
+impl Dependencies {
+    /// Records `scheduled`, the cases that will run. Only they are waited for.
+    pub fn schedule(&self, scheduled: &[&'static TestCase]) {
+        let _ = self
+            .scheduled
+            .set(scheduled.iter().map(|case| full_name(case)).collect());
+    }
+
+    /// Waits until the scheduled dependencies of `case` have finished. Returns why `case` must
+    /// be skipped if any of them did not pass.
+    pub fn wait(&self, case: &TestCase) -> Result<(), String> {
+        let Some(scheduled) = self.scheduled.get() else {
+            return Ok(());
+        };
+        // Cases run under `catch_unwind`, but a poisoned lock must not stop the others anyway.
+        let mut outcomes = self
+            .outcomes
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        for name in case.dependencies().filter(|name| scheduled.contains(name)) {
+            loop {
+                match outcomes.get(&name) {
+                    Some(Outcome::Passed) => break,
+                    Some(Outcome::Failed) => return Err(format!("dependency `{}` failed", name)),
+                    Some(Outcome::Skipped) => {
+                        return Err(format!("dependency `{}` was skipped", name));
+                    }
+                    None => {
+                        outcomes = self
+                            .finished
+                            .wait(outcomes)
+                            .unwrap_or_else(|poisoned| poisoned.into_inner());
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Records how `case` ended and wakes up the cases waiting for it.
+    pub fn record(&self, case: &TestCase, outcome: Outcome) {
+        self.outcomes
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .insert(full_name(case), outcome);
+        self.finished.notify_all();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Dependencies, Outcome, check, order};
+    use crate::test_support::DEFAULT_CASE;
+    use satchel::test_harness::TestCase;
+
+    static QUERY: TestCase = TestCase {
+        name: "query_users",
+        depends_on: &["migrate_schema"],
+        ..DEFAULT_CASE
+    };
+    static REPORT: TestCase = TestCase {
+        name: "report",
+        depends_on: &["query_users", "crate::tests::connect"],
+        ..DEFAULT_CASE
+    };
+    static MIGRATE: TestCase = TestCase {
+        name: "migrate_schema",
+        depends_on: &["connect"],
+        ..DEFAULT_CASE
+    };
+    static CONNECT: TestCase = TestCase {
+        name: "connect",
+        ..DEFAULT_CASE
+    };
+    static UNRELATED: TestCase = TestCase {
+        name: "unrelated",
+        ..DEFAULT_CASE
+    };
+
+    #[test]
+    fn dependencies_move_up_before_their_dependents() {
+        let mut cases = vec![&UNRELATED, &REPORT, &QUERY, &MIGRATE, &CONNECT];
+        check(&cases).unwrap();
+        order(&mut cases);
+        let names: Vec<&str> = cases.iter().map(|case| case.name).collect();
+        assert_eq!(
+            names,
+            [
+                "unrelated",
+                "connect",
+                "migrate_schema",
+                "query_users",
+                "report"
+            ]
+        );
+    }
+
+    #[test]
+    fn unknown_dependencies_and_cycles_are_rejected() {
+        assert_eq!(
+            check(&[&QUERY]).unwrap_err(),
+            "mycrate::tests::query_users depends on `mycrate::tests::migrate_schema`, which is \
+             not a registered test"
+        );
+        static FIRST: TestCase = TestCase {
+            name: "first",
+            depends_on: &["second"],
+            ..DEFAULT_CASE
+        };
+        static SECOND: TestCase = TestCase {
+            name: "second",
+            depends_on: &["first"],
+            ..DEFAULT_CASE
+        };
+        assert_eq!(
+            check(&[&UNRELATED, &FIRST, &SECOND]).unwrap_err(),
+            "tests depend on each other in a cycle: mycrate::tests::first -> \
+             mycrate::tests::second -> mycrate::tests::first"
+        );
+    }
+
+    #[test]
+    fn dependents_wait_and_are_skipped_after_a_failure() {
+        let dependencies = Dependencies::default();
+        dependencies.schedule(&[&CONNECT, &MIGRATE, &QUERY, &REPORT]);
+        std::thread::scope(|scope| {
+            let query = scope.spawn(|| dependencies.wait(&QUERY));
+            dependencies.record(&CONNECT, Outcome::Passed);
+            assert_eq!(dependencies.wait(&MIGRATE), Ok(()));
+            dependencies.record(&MIGRATE, Outcome::Failed);
+            assert_eq!(
+                query.join().unwrap(),
+                Err("dependency `mycrate::tests::migrate_schema` failed".to_string())
+            );
+        });
+        dependencies.record(&QUERY, Outcome::Skipped);
+        assert_eq!(
+            dependencies.wait(&REPORT),
+            Err("dependency `mycrate::tests::query_users` was skipped".to_string())
+        );
+    }
+
+    #[test]
+    fn dependencies_outside_the_run_are_not_waited_for() {
+        let dependencies = Dependencies::default();
+        dependencies.schedule(&[&QUERY]);
+        assert_eq!(dependencies.wait(&QUERY), Ok(()));
+    }
+}
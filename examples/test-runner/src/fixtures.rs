@@ -0,0 +1,322 @@
+//! Setup and teardown of the fixtures requested by the cases of one run.
+//!
+//! Test-scoped values are created for every case and dropped right after it. Module- and
+//! run-scoped values are created by the first case that needs them; a module-scoped value is
+//! dropped once the last scheduled case of its module that uses it has finished, everything else
+//! when the run finishes. Teardown runs whether the test passed, failed or panicked.
+//!
+//! A case that runs in a child process, with `--isolate`, because its output is captured while
+//! tests run in parallel or because it has a timeout, gets a cache of its own there, so even its
+//! module- and run-scoped values are set up for it alone. The cache of the parent counts it as
+//! done with them through [`FixtureCache::skip`].
+
+use crate::describe_panic;
+use libtest_mimic::Failed;
+use satchel::test_harness::TestCase;
+use satchel::{Fixture, FixtureScope, FixtureValue, FixtureValues};
+use std::collections::HashMap;
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex, OnceLock};
+
+// A shared value is identified by the address of its fixture and, for module scope, the module
+// of the tests using it.
+type Key = (usize, &'static str);
+
+type Slot = Arc<OnceLock<Result<FixtureValue, String>>>;
+
+struct Shared {
+    fixture: &'static Fixture,
+    slot: Slot,
+    // Scheduled cases that still have to release the value.
+    users: usize,
+}
+
+#[derive(Default)]
+pub(crate) struct FixtureCache {
+    shared: Mutex<HashMap<Key, Shared>>,
+    module_users: OnceLock<HashMap<Key, usize>>,
+    teardown_failed: AtomicBool,
+}
+
+fn key(fixture: &'static Fixture, case: &TestCase) -> Key {
+    let module = match fixture.scope {
+        FixtureScope::Module => case.module_path,
+        FixtureScope::Test | FixtureScope::Run => "",
+    };
+    (std::ptr::from_ref(fixture) as usize, module)
+}
+
+fn setup(fixture: &'static Fixture) -> Result<FixtureValue, String> {
+    let error = match panic::catch_unwind(fixture.setup) {
+        Ok(Ok(value)) => return Ok(value),
+        Ok(Err(error)) => error.debug,
+        Err(payload) => describe_panic(&*payload),
+    };
+    Err(format!(
+        "Fixture `{}` failed to set up: {}\n  at {}",
+        fixture.name, error, fixture.location
+    ))
+}
+
+/// Drops `value`, returning the panic message if its teardown panicked.
+fn teardown<T>(value: T) -> Result<(), String> {
+    panic::catch_unwind(AssertUnwindSafe(|| drop(value)))
+        .map_err(|payload| describe_panic(&*payload))
+}
+
+impl FixtureCache {
+    /// Records `scheduled`, the cases that will run, so that module-scoped values can be torn
+    /// down as soon as the last of their users has finished. Without it, module-scoped values
+    /// live until [`FixtureCache::finish`] like run-scoped ones.
+    pub fn schedule(&self, scheduled: &[&'static TestCase]) {
+        let mut module_users = HashMap::new();
+        for case in scheduled {
+            for fixture in case.fixtures {
+                if fixture.scope == FixtureScope::Module {
+                    *module_users.entry(key(fixture, case)).or_default() += 1;
+                }
+            }
+        }
+        let _ = self.module_users.set(module_users);
+    }
+
+    fn shared_entry<'a>(
+        &self,
+        shared: &'a mut HashMap<Key, Shared>,
+        fixture: &'static Fixture,
+        case: &TestCase,
+    ) -> &'a mut Shared {
+        let key = key(fixture, case);
+        shared.entry(key).or_insert_with(|| Shared {
+            fixture,
+            slot: Slot::default(),
+            users: self
+                .module_users
+                .get()
+                .and_then(|users| users.get(&key).copied())
+                .unwrap_or(usize::MAX),
+        })
+    }
+
+    fn shared_slot(&self, fixture: &'static Fixture, case: &TestCase) -> Slot {
+        let mut shared = self.shared.lock().unwrap();
+        Arc::clone(&self.shared_entry(&mut shared, fixture, case).slot)
+    }
+
+    /// Counts `case` as one more user of its module-scoped values, so they outlive the release
+    /// of an attempt that may be retried. The extra use ends with another
+    /// [`FixtureCache::release`], by the next attempt or without values if there is none.
+    pub fn expect_retry(&self, case: &'static TestCase) {
+        let mut shared = self.shared.lock().unwrap();
+        for fixture in case.fixtures {
+            if fixture.scope == FixtureScope::Module {
+                let entry = self.shared_entry(&mut shared, fixture, case);
+                entry.users = entry.users.saturating_add(1);
+            }
+        }
+    }
+
+    /// Sets up or looks up the values of the fixtures `case` requested. Every call must be
+    /// followed by [`FixtureCache::release`], even if it fails.
+    pub fn acquire(&self, case: &'static TestCase) -> Result<FixtureValues, Failed> {
+        let mut values = Vec::with_capacity(case.fixtures.len());
+        for fixture in case.fixtures {
+            let value = match fixture.scope {
+                FixtureScope::Test => setup(fixture),
+                FixtureScope::Module | FixtureScope::Run => self
+                    .shared_slot(fixture, case)
+                    .get_or_init(|| setup(fixture))
+                    .clone(),
+            };
+            match value {
+                Ok(value) => values.push(value),
+                Err(message) => {
+                    // Values set up so far are torn down in reverse order, like after the test.
+                    for value in values.into_iter().rev() {
+                        let _ = teardown(value);
+                    }
+                    return Err(Failed::from(message));
+                }
+            }
+        }
+        Ok(FixtureValues::new(values))
+    }
+
+    /// Tears down the test-scoped values of `case` and module-scoped values it was the last user
+    /// of. Returns an error if a test-scoped teardown panicked.
+    pub fn release(
+        &self,
+        case: &'static TestCase,
+        values: Option<FixtureValues>,
+    ) -> Result<(), Failed> {
+        let mut result = Ok(());
+        let values = values.map(FixtureValues::into_inner).unwrap_or_default();
+        for (fixture, value) in case.fixtures.iter().zip(values).rev() {
+            if let Err(message) = teardown(value)
+                && fixture.scope == FixtureScope::Test
+            {
+                result = Err(Failed::from(format!(
+                    "Fixture `{}` failed to tear down: {}\n  at {}",
+                    fixture.name, message, fixture.location
+                )));
+            }
+        }
+
+        for fixture in case.fixtures {
+            if fixture.scope != FixtureScope::Module {
+                continue;
+            }
+            let key = key(fixture, case);
+            let finished = {
+                let mut shared = self.shared.lock().unwrap();
+                match shared.get_mut(&key) {
+                    Some(entry) if entry.users > 1 => {
+                        entry.users -= 1;
+                        None
+                    }
+                    Some(_) => shared.remove(&key),
+                    None => None,
+                }
+            };
+            if let Some(entry) = finished {
+                self.teardown_shared(entry);
+            }
+        }
+        result
+    }
+
+    fn teardown_shared(&self, Shared { fixture, slot, .. }: Shared) {
+        if let Err(message) = teardown(slot) {
+            self.teardown_failed.store(true, Ordering::Relaxed);
+            eprintln!(
+                "error: fixture `{}` failed to tear down: {}\n  at {}",
+                fixture.name, message, fixture.location
+            );
+        }
+    }
+
+    /// Counts `case`, which is skipped or runs in a child process, as done with its module-scoped
+    /// values, so they do not wait for it to be torn down.
+    pub fn skip(&self, case: &'static TestCase) {
+        {
+            let mut shared = self.shared.lock().unwrap();
+            for fixture in case.fixtures {
+                if fixture.scope == FixtureScope::Module {
+                    self.shared_entry(&mut shared, fixture, case);
+                }
+            }
+        }
+        let _ = self.release(case, None);
+    }
+
+    /// Tears down the remaining shared values. Returns `false` if any shared teardown panicked
+    /// during the run.
+    pub fn finish(&self) -> bool {
+        let remaining: Vec<_> = self.shared.lock().unwrap().drain().collect();
+        for (_, entry) in remaining {
+            self.teardown_shared(entry);
+        }
+        !self.teardown_failed.load(Ordering::Relaxed)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::FixtureCache;
+    use crate::test_support::DEFAULT_CASE;
+    use satchel::test_harness::{Fixture, SourceLocation, TestCase};
+    use satchel::{FixtureScope, FixtureValue, TestError};
+    use std::sync::Arc;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    const LOCATION: SourceLocation = SourceLocation {
+        file: "src/lib.rs",
+        line: 1,
+        column: 1,
+    };
+
+    static SETUPS: AtomicUsize = AtomicUsize::new(0);
+    static TEARDOWNS: AtomicUsize = AtomicUsize::new(0);
+
+    struct Counted;
+
+    impl Drop for Counted {
+        fn drop(&mut self) {
+            TEARDOWNS.fetch_add(1, Ordering::SeqCst);
+        }
+    }
+
+    fn counted() -> Result<FixtureValue, TestError> {
+        SETUPS.fetch_add(1, Ordering::SeqCst);
+        Ok(Arc::new(Counted))
+    }
+
+    fn refused() -> Result<FixtureValue, TestError> {
+        Err(TestError {
+            debug: "\"connection refused\"".to_string(),
+        })
+    }
+
+    static SHARED: Fixture = Fixture {
+        name: "shared",
+        module_path: "mycrate::tests",
+        location: LOCATION,
+        scope: FixtureScope::Module,
+        setup: counted,
+    };
+
+    static BROKEN: Fixture = Fixture {
+        name: "database",
+        module_path: "mycrate::tests",
+        location: LOCATION,
+        scope: FixtureScope::Test,
+        setup: refused,
+    };
+
+    static FIRST: TestCase = TestCase {
+        name: "first",
+        fixtures: &[&SHARED],
+        ..DEFAULT_CASE
+    };
+    static SECOND: TestCase = TestCase {
+        name: "second",
+        fixtures: &[&SHARED],
+        ..DEFAULT_CASE
+    };
+    static FAILING: TestCase = TestCase {
+        name: "failing",
+        fixtures: &[&SHARED, &BROKEN],
+        ..DEFAULT_CASE
+    };
+
+    #[test]
+    fn module_values_are_shared_and_torn_down_after_the_last_user() {
+        let cache = FixtureCache::default();
+        cache.schedule(&[&FIRST, &SECOND, &FAILING]);
+
+        cache.expect_retry(&FIRST);
+        let first = cache.acquire(&FIRST).unwrap();
+        let second = cache.acquire(&SECOND).unwrap();
+        assert_eq!(SETUPS.load(Ordering::SeqCst), 1);
+        cache.release(&FIRST, Some(first)).unwrap();
+        // The value outlives the first attempt of a case that may be retried.
+        let first = cache.acquire(&FIRST).unwrap();
+        cache.release(&FIRST, Some(first)).unwrap();
+        cache.release(&SECOND, Some(second)).unwrap();
+        assert_eq!(TEARDOWNS.load(Ordering::SeqCst), 0);
+
+        // The failed setup of the last user still releases the shared value.
+        let failed = cache.acquire(&FAILING).unwrap_err();
+        assert_eq!(
+            failed.message(),
+            Some(
+                "Fixture `database` failed to set up: \"connection refused\"\n  at src/lib.rs:1:1"
+            )
+        );
+        cache.release(&FAILING, None).unwrap();
+        assert_eq!(TEARDOWNS.load(Ordering::SeqCst), 1);
+        assert!(cache.finish());
+        assert_eq!(SETUPS.load(Ordering::SeqCst), 1);
+    }
+}
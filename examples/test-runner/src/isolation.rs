@@ -0,0 +1,343 @@
+//! Process isolation: with `--isolate`, every case runs in a fresh copy of the current
+//! executable, so an abort, a crash in foreign code or `std::process::exit` fails that case
+//! instead of taking down the host and the remaining tests. A case with a timeout always runs
+//! this way, so that a case exceeding it ends with its process instead of running on. The child
+//! enforces the timeout itself; if it has not ended shortly after, for example because a fixture
+//! hangs in its setup, the parent kills it. Tests whose output is captured while they run in
+//! parallel also run this way, since redirecting their output would capture the whole process.
+//!
+//! The child is started with the arguments of the parent and the environment variables below;
+//! its call to [`run_tests`](crate::run_tests) runs the requested case, writes the outcome to a
+//! result file and exits. A child that ends without writing the file crashed or exited early.
+
+use crate::bench::BenchResult;
+use crate::capture::Output;
+use crate::fixtures::FixtureCache;
+use crate::{CaseError, execute_case, full_name, with_location};
+use libtest_mimic::{Failed, Measurement};
+use satchel::Throughput;
+use satchel::test_harness::TestCase;
+use std::io::{self, Read, Write};
+use std::path::PathBuf;
+use std::process::{Child, Command, ExitStatus, Stdio};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
+
+/// Full name (`module_path::name`) of the case the child runs.
+const CASE_ENV: &str = "SATCHEL_ISOLATED_CASE";
+/// File the child writes the outcome to.
+const RESULT_ENV: &str = "SATCHEL_ISOLATED_RESULT";
+/// Present if the child should measure a benchmark instead of running it once.
+const BENCH_ENV: &str = "SATCHEL_ISOLATED_BENCH";
+
+/// Time a child gets beyond the timeout of its case to start, set up its fixtures and report the
+/// timeout itself before the parent kills it.
+const KILL_MARGIN: Duration = Duration::from_secs(2);
+/// Interval at which the parent checks whether a child with a deadline has exited.
+const POLL_INTERVAL: Duration = Duration::from_millis(10);
+
+/// Name of the case to run if this process is an isolated child.
+pub(crate) fn requested_case() -> Option<String> {
+    std::env::var(CASE_ENV).ok()
+}
+
+/// Runs the case called `name` and exits the process, if it is one of `cases`. Returns
+/// otherwise, as the case may belong to another library linked into the same host.
+pub(crate) fn run_requested_case(cases: &[&'static TestCase], name: &str) {
+    let Some(case) = cases.iter().find(|case| full_name(case) == name) else {
+        return;
+    };
+    // The child runs a single case, so every fixture scope is set up for it alone.
+    let fixtures = FixtureCache::default();
+    let outcome = match execute_case(case, std::env::var_os(BENCH_ENV).is_none(), &fixtures) {
+        Ok(None) => "passed".to_string(),
+        Ok(Some(result)) => {
+            let Measurement { avg, variance } = result.measurement;
+            match result.throughput {
+                None => format!("measured {} {}", avg, variance),
+                Some(Throughput::Bytes(bytes)) => {
+                    format!("measured {} {} bytes {}", avg, variance, bytes)
+                }
+                Some(Throughput::Elements(elements)) => {
+                    format!("measured {} {} elements {}", avg, variance, elements)
+                }
+            }
+        }
+        Err(CaseError::Failed(failed)) => {
+            format!("failed\n{}", failed.message().unwrap_or_default())
+        }
+        Err(CaseError::Fixture(failed)) => {
+            format!("error\n{}", failed.message().unwrap_or_default())
+        }
+        Err(CaseError::Skipped(reason)) => format!("skipped\n{}", reason.unwrap_or_default()),
+    };
+    let outcome = if fixtures.finish() {
+        outcome
+    } else {
+        "error\nA shared fixture failed to tear down".to_string()
+    };
+    let written =
+        std::env::var_os(RESULT_ENV).is_some_and(|path| std::fs::write(path, outcome).is_ok());
+    let _ = std::io::stdout().flush();
+    std::process::exit(if written { 0 } else { 1 });
+}
+
+fn result_path() -> PathBuf {
+    static NEXT: AtomicUsize = AtomicUsize::new(0);
+    std::env::temp_dir().join(format!(
+        "satchel-{}-{}.result",
+        std::process::id(),
+        NEXT.fetch_add(1, Ordering::Relaxed)
+    ))
+}
+
+/// Runs `case` in a child process and returns its outcome, with the output of the child if
+/// `capture` is set. Otherwise the child writes to the streams of this process.
+pub(crate) fn run_isolated(
+    case: &'static TestCase,
+    test_mode: bool,
+    capture: bool,
+) -> (Result<Option<BenchResult>, CaseError>, Option<Output>) {
+    let path = result_path();
+    let mut command = Command::new(match std::env::current_exe() {
+        Ok(exe) => exe,
+        Err(e) => {
+            let failed = Failed::from(format!("Failed to locate the test executable: {}", e));
+            return (Err(failed.into()), None);
+        }
+    });
+    command
+        .args(std::env::args_os().skip(1))
+        .env(CASE_ENV, full_name(case))
+        .env(RESULT_ENV, &path)
+        .stdin(Stdio::null());
+    if !test_mode {
+        command.env(BENCH_ENV, "1");
+    }
+
+    if capture {
+        command.stdout(Stdio::piped()).stderr(Stdio::piped());
+    }
+    let mut child = match command.spawn() {
+        Ok(child) => child,
+        Err(e) => {
+            let failed = Failed::from(format!("Failed to start the test process: {}", e));
+            return (Err(failed.into()), None);
+        }
+    };
+    // The pipes are read while waiting, so a child writing more than they hold does not block.
+    let stdout = child.stdout.take().map(read_in_background);
+    let stderr = child.stderr.take().map(read_in_background);
+    let deadline = case
+        .timeout
+        .map(|timeout| Instant::now() + timeout + KILL_MARGIN);
+    let status = wait(&mut child, deadline);
+    let output = capture.then(|| Output {
+        stdout: stdout.map(collect).unwrap_or_default(),
+        stderr: stderr.map(collect).unwrap_or_default(),
+    });
+    let status = match status {
+        Ok(Some(status)) => status,
+        Ok(None) => {
+            let _ = std::fs::remove_file(&path);
+            let failed = Failed::from(format!(
+                "Test {} timed out after {:?}; its process was killed",
+                full_name(case),
+                case.timeout.unwrap_or_default()
+            ));
+            return (
+                Err(CaseError::Failed(with_location(failed, case.location))),
+                output,
+            );
+        }
+        Err(e) => {
+            let failed = Failed::from(format!("Failed to wait for the test process: {}", e));
+            return (Err(failed.into()), output);
+        }
+    };
+    let outcome = std::fs::read_to_string(&path).ok();
+    let _ = std::fs::remove_file(&path);
+
+    let result = outcome
+        .as_deref()
+        .and_then(parse_outcome)
+        .unwrap_or_else(|| {
+            Err(CaseError::Failed(with_location(
+                Failed::from(describe_exit(status)),
+                case.location,
+            )))
+        });
+    (result, output)
+}
+
+/// Waits for `child` to exit, killing it once `deadline` has passed. Returns `None` if it was
+/// killed.
+fn wait(child: &mut Child, deadline: Option<Instant>) -> io::Result<Option<ExitStatus>> {
+    let Some(deadline) = deadline else {
+        return child.wait().map(Some);
+    };
+    loop {
+        if let Some(status) = child.try_wait()? {
+            return Ok(Some(status));
+        }
+        if Instant::now() >= deadline {
+            // The child may have exited in the meantime; waiting reaps it either way.
+            let _ = child.kill();
+            child.wait()?;
+            return Ok(None);
+        }
+        thread::sleep(POLL_INTERVAL);
+    }
+}
+
+fn read_in_background(mut pipe: impl Read + Send + 'static) -> JoinHandle<String> {
+    thread::spawn(move || {
+        let mut bytes = Vec::new();
+        let _ = pipe.read_to_end(&mut bytes);
+        String::from_utf8_lossy(&bytes).into_owned()
+    })
+}
+
+fn collect(reader: JoinHandle<String>) -> String {
+    reader.join().unwrap_or_default()
+}
+
+/// Parses the result file written by [`run_requested_case`].
+fn parse_outcome(outcome: &str) -> Option<Result<Option<BenchResult>, CaseError>> {
+    if outcome == "passed" {
+        return Some(Ok(None));
+    }
+    if let Some(message) = outcome.strip_prefix("failed\n") {
+        return Some(Err(CaseError::Failed(Failed::from(message))));
+    }
+    if let Some(message) = outcome.strip_prefix("error\n") {
+        return Some(Err(CaseError::Fixture(Failed::from(message))));
+    }
+    if let Some(reason) = outcome.strip_prefix("skipped\n") {
+        let reason = (!reason.is_empty()).then(|| reason.to_string());
+        return Some(Err(CaseError::Skipped(reason)));
+    }
+    let mut fields = outcome.strip_prefix("measured ")?.split(' ');
+    let measurement = Measurement {
+        avg: fields.next()?.parse().ok()?,
+        variance: fields.next()?.parse().ok()?,
+    };
+    let throughput = match (fields.next(), fields.next()) {
+        (None, None) => None,
+        (Some("bytes"), Some(bytes)) => Some(Throughput::Bytes(bytes.parse().ok()?)),
+        (Some("elements"), Some(elements)) => Some(Throughput::Elements(elements.parse().ok()?)),
+        _ => return None,
+    };
+    Some(Ok(Some(BenchResult {
+        measurement,
+        throughput,
+    })))
+}
+
+/// Explains why a child ended without reporting an outcome.
+fn describe_exit(status: ExitStatus) -> String {
+    #[cfg(unix)]
+    {
+        use std::os::unix::process::ExitStatusExt;
+        if let Some(signal) = status.signal() {
+            return match signal_name(signal) {
+                Some(name) => format!("Test process was killed by signal {} ({})", signal, name),
+                None => format!("Test process was killed by signal {}", signal),
+            };
+        }
+    }
+    match status.code() {
+        // Windows reports crashes as NTSTATUS codes such as 0xC0000005.
+        Some(code) if code < 0 => format!("Test process crashed with code {:#010x}", code),
+        Some(code) => format!(
+            "Test process exited with status {} before reporting a result",
+            code
+        ),
+        None => format!("Test process ended without reporting a result: {}", status),
+    }
+}
+
+#[cfg(unix)]
+fn signal_name(signal: i32) -> Option<&'static str> {
+    Some(match signal {
+        libc::SIGABRT => "SIGABRT",
+        libc::SIGSEGV => "SIGSEGV",
+        libc::SIGBUS => "SIGBUS",
+        libc::SIGFPE => "SIGFPE",
+        libc::SIGILL => "SIGILL",
+        libc::SIGTRAP => "SIGTRAP",
+        libc::SIGKILL => "SIGKILL",
+        libc::SIGTERM => "SIGTERM",
+        libc::SIGINT => "SIGINT",
+        libc::SIGPIPE => "SIGPIPE",
+        _ => return None,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{describe_exit, parse_outcome};
+    use crate::CaseError;
+    use satchel::Throughput;
+
+    #[test]
+    fn outcomes_are_parsed() {
+        assert!(matches!(parse_outcome("passed"), Some(Ok(None))));
+        let result = parse_outcome("measured 1200 35").unwrap().unwrap().unwrap();
+        assert_eq!(
+            (result.measurement.avg, result.measurement.variance),
+            (1200, 35)
+        );
+        assert_eq!(result.throughput, None);
+        let result = parse_outcome("measured 1200 35 bytes 4096")
+            .unwrap()
+            .unwrap()
+            .unwrap();
+        assert_eq!(result.throughput, Some(Throughput::Bytes(4096)));
+        let failed = parse_outcome("failed\nUnexpected panic: boom\n  at src/lib.rs:1:1");
+        let Some(Err(CaseError::Failed(failed))) = failed else {
+            panic!("expected a failure, got {:?}", failed);
+        };
+        assert_eq!(
+            failed.message(),
+            Some("Unexpected panic: boom\n  at src/lib.rs:1:1")
+        );
+        let error = parse_outcome("error\nFixture `db` failed to set up: refused");
+        assert!(
+            matches!(error, Some(Err(CaseError::Fixture(_)))),
+            "{:?}",
+            error
+        );
+        assert!(matches!(
+            parse_outcome("skipped\nno device"),
+            Some(Err(CaseError::Skipped(Some(reason)))) if reason == "no device"
+        ));
+        assert!(matches!(
+            parse_outcome("skipped\n"),
+            Some(Err(CaseError::Skipped(None)))
+        ));
+        assert!(parse_outcome("measured 1200").is_none());
+        assert!(parse_outcome("").is_none());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn signals_are_named() {
+        use std::os::unix::process::ExitStatusExt;
+        use std::process::ExitStatus;
+
+        // A raw wait status holds the signal in its low bits and the exit code above them.
+        assert_eq!(
+            describe_exit(ExitStatus::from_raw(libc::SIGSEGV)),
+            format!(
+                "Test process was killed by signal {} (SIGSEGV)",
+                libc::SIGSEGV
+            )
+        );
+        assert_eq!(
+            describe_exit(ExitStatus::from_raw(3 << 8)),
+            "Test process exited with status 3 before reporting a result"
+        );
+    }
+}
@@ -0,0 +1,320 @@
+//! `--format json`: one JSON object per line, in the format of libtest's unstable
+//! `--format json`, so tools reading `cargo test -- -Z unstable-options --format json`
+//! (cargo2junit, IDEs) can read satchel runs too.
+//!
+//! ```json
+//! { "type": "suite", "event": "started", "test_count": 2 }
+//! { "type": "test", "event": "started", "name": "somelib::tests::it_works" }
+//! { "type": "test", "name": "somelib::tests::it_works", "event": "ok", "exec_time": 0.001, "location": {"column":5,"file":"src/lib.rs","line":12} }
+//! { "type": "bench", "name": "somelib::tests::bench_add", "median": 1200, "deviation": 35 }
+//! { "type": "suite", "event": "ok", "passed": 1, "failed": 0, "ignored": 0, "measured": 1, "filtered_out": 0, "exec_time": 1.52 }
+//! ```
+//!
+//! Failed tests carry the failure message, followed by the output they wrote, in both `stdout`,
+//! as libtest does, and `message`; passing tests carry their output in `stdout`. Ignored and
+//! skipped tests carry the reason in `message`. `location` is satchel's addition, as are
+//! `attempts` and `flaky` on tests that were retried and the `flaky` count of the suite.
+//! Libtest-mimic's own output is discarded.
+
+use crate::full_name;
+use crate::report::{CaseReport, Status};
+use satchel::Throughput;
+use satchel::test_harness::TestCase;
+use serde_json::{Value, json};
+use std::fmt;
+use std::io::{self, Write};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+/// Null device that libtest-mimic writes its human output to while the events are printed.
+pub(crate) const NULL_DEVICE: &str = if cfg!(windows) { "NUL" } else { "/dev/null" };
+
+/// Writer of the event stream, shared by the threads running the tests.
+#[derive(Clone)]
+pub(crate) struct Events {
+    out: Arc<Mutex<Box<dyn Write + Send>>>,
+}
+
+impl fmt::Debug for Events {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Events").finish_non_exhaustive()
+    }
+}
+
+impl Events {
+    pub fn new(out: impl Write + Send + 'static) -> Self {
+        Events {
+            out: Arc::new(Mutex::new(Box::new(out))),
+        }
+    }
+
+    /// Writes one event with its fields in the given order. Events are best effort, like the
+    /// output of libtest-mimic.
+    fn emit(&self, fields: &[(&str, Value)]) {
+        let fields: Vec<String> = fields
+            .iter()
+            .map(|(key, value)| format!("\"{}\": {}", key, value))
+            .collect();
+        let mut out = self.out.lock().unwrap();
+        let _ = writeln!(out, "{{ {} }}", fields.join(", "));
+        let _ = out.flush();
+    }
+
+    pub fn suite_started(&self, test_count: usize) {
+        self.emit(&[
+            ("type", json!("suite")),
+            ("event", json!("started")),
+            ("test_count", json!(test_count)),
+        ]);
+    }
+
+    pub fn test_started(&self, case: &TestCase) {
+        self.emit(&[
+            ("type", json!("test")),
+            ("event", json!("started")),
+            ("name", json!(full_name(case))),
+        ]);
+    }
+
+    /// Writes the outcome of a case: a `bench` event for a measurement, a `test` event
+    /// otherwise.
+    pub fn test_finished(&self, report: &CaseReport) {
+        let name = json!(full_name(report.case));
+        if let Status::Measured(result) = &report.status {
+            let mut fields = vec![
+                ("type", json!("bench")),
+                ("name", name),
+                ("median", json!(result.measurement.avg)),
+                ("deviation", json!(result.measurement.variance)),
+            ];
+            if let Some(Throughput::Bytes(bytes)) = result.throughput
+                && result.measurement.avg > 0
+            {
+                let per_second = bytes as f64 * 1e9 / result.measurement.avg as f64;
+                fields.push(("mib_per_second", json!((per_second / 1048576.0) as u64)));
+            }
+            self.emit(&fields);
+            return;
+        }
+
+        let event = match report.status {
+            Status::Passed => "ok",
+            Status::Failed | Status::Error => "failed",
+            Status::Skipped(_) => "ignored",
+            Status::Measured(_) => unreachable!("measurements are bench events"),
+        };
+        let mut fields = vec![
+            ("type", json!("test")),
+            ("name", name),
+            ("event", json!(event)),
+            ("exec_time", json!(report.duration.as_secs_f64())),
+        ];
+        // A failure message already ends with the output the case wrote.
+        let stdout = match &report.message {
+            Some(message) => Some(format!("{}\n", message)),
+            None => report.stdout.clone(),
+        };
+        if let Some(stdout) = stdout.filter(|stdout| !stdout.is_empty()) {
+            fields.push(("stdout", json!(stdout)));
+        }
+        let message = match &report.status {
+            Status::Skipped(reason) => reason.as_ref(),
+            _ => report.message.as_ref(),
+        };
+        if let Some(message) = message {
+            fields.push(("message", json!(message)));
+        }
+        if !report.retried.is_empty() {
+            fields.push(("attempts", json!(report.retried.len() + 1)));
+            fields.push(("flaky", json!(report.is_flaky())));
+        }
+        let location = report.case.location;
+        fields.push((
+            "location",
+            json!({ "file": location.file, "line": location.line, "column": location.column }),
+        ));
+        self.emit(&fields);
+    }
+
+    /// Writes the final event with the totals of `reports`. `passed` is the verdict of the
+    /// whole run, which also covers fixture teardown and baselines.
+    pub fn suite_finished(
+        &self,
+        reports: &[CaseReport],
+        filtered_out: usize,
+        passed: bool,
+        exec_time: Duration,
+    ) {
+        let count = |matches: fn(&Status) -> bool| {
+            reports
+                .iter()
+                .filter(|report| matches(&report.status))
+                .count()
+        };
+        self.emit(&[
+            ("type", json!("suite")),
+            ("event", json!(if passed { "ok" } else { "failed" })),
+            ("passed", json!(count(|s| *s == Status::Passed))),
+            (
+                "failed",
+                json!(count(|s| matches!(s, Status::Failed | Status::Error))),
+            ),
+            ("ignored", json!(count(|s| matches!(s, Status::Skipped(_))))),
+            (
+                "measured",
+                json!(count(|s| matches!(s, Status::Measured(_)))),
+            ),
+            (
+                "flaky",
+                json!(reports.iter().filter(|report| report.is_flaky()).count()),
+            ),
+            ("filtered_out", json!(filtered_out)),
+            ("exec_time", json!(exec_time.as_secs_f64())),
+        ]);
+    }
+}
+
+/// Events to stdout, or to `logfile` if libtest's `--logfile` was given.
+pub(crate) fn open(logfile: Option<&str>) -> io::Result<Events> {
+    Ok(match logfile {
+        Some(path) => Events::new(std::fs::File::create(path)?),
+        None => Events::new(io::stdout()),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Events;
+    use crate::bench::BenchResult;
+    use crate::report::{CaseReport, Status};
+    use crate::test_support::DEFAULT_CASE;
+    use libtest_mimic::Measurement;
+    use satchel::Throughput;
+    use satchel::test_harness::{SourceLocation, TestCase};
+    use serde_json::Value;
+    use std::io::Write;
+    use std::sync::{Arc, Mutex};
+    use std::time::Duration;
+
+    #[derive(Clone, Default)]
+    struct Buffer(Arc<Mutex<Vec<u8>>>);
+
+    impl Write for Buffer {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.0.lock().unwrap().write(buf)
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    static CASE: TestCase = TestCase {
+        location: SourceLocation {
+            file: "src/lib.rs",
+            line: 12,
+            column: 5,
+        },
+        ..DEFAULT_CASE
+    };
+
+    fn report(status: Status, message: Option<&str>, stdout: Option<&str>) -> CaseReport {
+        CaseReport {
+            case: &CASE,
+            status,
+            duration: Duration::from_millis(250),
+            message: message.map(str::to_string),
+            stdout: stdout.map(str::to_string),
+            stderr: None,
+            retried: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn events_follow_the_libtest_format() {
+        let buffer = Buffer::default();
+        let events = Events::new(buffer.clone());
+        let measured = Status::Measured(BenchResult {
+            measurement: Measurement {
+                avg: 1000,
+                variance: 10,
+            },
+            throughput: Some(Throughput::Bytes(1 << 20)),
+        });
+        let reports = [
+            report(Status::Passed, None, None),
+            report(
+                Status::Failed,
+                Some("boom\n  at src/lib.rs:12:5\n---- stdout ----\nout"),
+                Some("out\n"),
+            ),
+            report(Status::Skipped(Some("needs a database".into())), None, None),
+            report(measured, None, None),
+            CaseReport {
+                retried: vec!["boom".to_string()],
+                ..report(Status::Passed, None, None)
+            },
+        ];
+        events.suite_started(reports.len());
+        events.test_started(&CASE);
+        for report in &reports {
+            events.test_finished(report);
+        }
+        events.suite_finished(&reports, 3, false, Duration::from_secs(2));
+
+        let text = String::from_utf8(buffer.0.lock().unwrap().clone()).unwrap();
+        let lines: Vec<&str> = text.lines().collect();
+        assert_eq!(
+            lines[0],
+            r#"{ "type": "suite", "event": "started", "test_count": 5 }"#
+        );
+        assert_eq!(
+            lines[1],
+            r#"{ "type": "test", "event": "started", "name": "mycrate::tests::it_works" }"#
+        );
+        assert_eq!(
+            lines[2],
+            r#"{ "type": "test", "name": "mycrate::tests::it_works", "event": "ok", "exec_time": 0.25, "location": {"column":5,"file":"src/lib.rs","line":12} }"#
+        );
+
+        let events: Vec<Value> = lines
+            .iter()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+        assert_eq!(events[3]["event"], "failed");
+        assert_eq!(
+            events[3]["stdout"],
+            "boom\n  at src/lib.rs:12:5\n---- stdout ----\nout\n"
+        );
+        assert_eq!(
+            events[3]["message"],
+            "boom\n  at src/lib.rs:12:5\n---- stdout ----\nout"
+        );
+        assert_eq!(events[4]["event"], "ignored");
+        assert_eq!(events[4]["message"], "needs a database");
+        assert_eq!(events[5]["type"], "bench");
+        assert_eq!(
+            (&events[5]["median"], &events[5]["mib_per_second"]),
+            (&Value::from(1000), &Value::from(1_000_000))
+        );
+        assert_eq!(
+            (
+                &events[6]["event"],
+                &events[6]["attempts"],
+                &events[6]["flaky"]
+            ),
+            (&Value::from("ok"), &Value::from(2), &Value::from(true))
+        );
+        assert_eq!(events[7]["event"], "failed");
+        assert_eq!(
+            (&events[7]["passed"], &events[7]["failed"]),
+            (&Value::from(2), &Value::from(1))
+        );
+        assert_eq!(
+            (&events[7]["ignored"], &events[7]["measured"]),
+            (&Value::from(1), &Value::from(1))
+        );
+        assert_eq!(events[7]["filtered_out"], 3);
+        assert_eq!(events[7]["flaky"], 1);
+    }
+}
@@ -0,0 +1,251 @@
+//! JUnit XML output, as consumed by GitLab, Jenkins and most other CI systems.
+
+use crate::report::{CaseReport, Status};
+use std::borrow::Cow;
+use std::fmt::Write as _;
+use std::io;
+use std::path::Path;
+use std::time::Duration;
+
+/// Replaces characters that are not allowed in XML text or attribute values.
+fn escape(text: &str) -> Cow<'_, str> {
+    if !text
+        .chars()
+        .any(|c| matches!(c, '&' | '<' | '>' | '"' | '\'') || is_invalid_xml_char(c))
+    {
+        return Cow::Borrowed(text);
+    }
+
+    let mut escaped = String::with_capacity(text.len() + 16);
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&apos;"),
+            c if is_invalid_xml_char(c) => escaped.push('\u{FFFD}'),
+            c => escaped.push(c),
+        }
+    }
+    Cow::Owned(escaped)
+}
+
+// XML 1.0 only allows tab, newline and carriage return below U+0020.
+fn is_invalid_xml_char(c: char) -> bool {
+    c < '\u{20}' && !matches!(c, '\t' | '\n' | '\r')
+}
+
+fn seconds(duration: Duration) -> String {
+    format!("{:.3}", duration.as_secs_f64())
+}
+
+/// Renders the reports as a JUnit document containing a single `<testsuite>`.
+///
+/// Each case becomes a `<testcase>` whose `classname` is the module path of the test.
+pub(crate) fn render(suite: &str, reports: &[CaseReport]) -> String {
+    let failures = reports
+        .iter()
+        .filter(|report| report.status == Status::Failed)
+        .count();
+    let errors = reports
+        .iter()
+        .filter(|report| report.status == Status::Error)
+        .count();
+    let skipped = reports
+        .iter()
+        .filter(|report| matches!(report.status, Status::Skipped(_)))
+        .count();
+    let total: Duration = reports.iter().map(|report| report.duration).sum();
+
+    let mut xml = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+    let _ = writeln!(
+        xml,
+        "<testsuites name=\"{suite}\" tests=\"{tests}\" failures=\"{failures}\" errors=\"{errors}\" skipped=\"{skipped}\" time=\"{time}\">",
+        suite = escape(suite),
+        tests = reports.len(),
+        time = seconds(total),
+    );
+    let _ = writeln!(
+        xml,
+        "  <testsuite name=\"{suite}\" tests=\"{tests}\" failures=\"{failures}\" errors=\"{errors}\" skipped=\"{skipped}\" time=\"{time}\">",
+        suite = escape(suite),
+        tests = reports.len(),
+        time = seconds(total),
+    );
+
+    for report in reports {
+        let case = report.case;
+        let _ = write!(
+            xml,
+            "    <testcase name=\"{}\" classname=\"{}\" time=\"{}\" file=\"{}\" line=\"{}\"",
+            escape(case.name),
+            escape(case.module_path),
+            seconds(report.duration),
+            escape(case.location.file),
+            case.location.line,
+        );
+
+        let mut body = String::new();
+        match &report.status {
+            Status::Passed | Status::Measured(_) => {}
+            Status::Failed | Status::Error => {
+                let element = if report.status == Status::Error {
+                    "error"
+                } else {
+                    "failure"
+                };
+                let message = report.message.as_deref().unwrap_or("");
+                let summary = message.lines().next().unwrap_or("");
+                let _ = writeln!(
+                    body,
+                    "      <{element} message=\"{}\">{}</{element}>",
+                    escape(summary),
+                    escape(message)
+                );
+            }
+            Status::Skipped(Some(reason)) => {
+                let _ = writeln!(body, "      <skipped message=\"{}\"/>", escape(reason));
+            }
+            Status::Skipped(None) => body.push_str("      <skipped/>\n"),
+        }
+        // Earlier attempts of a retried case, in the format of Maven Surefire's reruns.
+        let rerun = if report.is_flaky() {
+            "flakyFailure"
+        } else {
+            "rerunFailure"
+        };
+        for message in &report.retried {
+            let summary = message.lines().next().unwrap_or("");
+            let _ = writeln!(
+                body,
+                "      <{rerun} message=\"{}\"><stackTrace>{}</stackTrace></{rerun}>",
+                escape(summary),
+                escape(message)
+            );
+        }
+        if let Some(stdout) = &report.stdout {
+            let _ = writeln!(body, "      <system-out>{}</system-out>", escape(stdout));
+        }
+        if let Some(stderr) = &report.stderr {
+            let _ = writeln!(body, "      <system-err>{}</system-err>", escape(stderr));
+        }
+
+        if body.is_empty() {
+            xml.push_str("/>\n");
+        } else {
+            xml.push_str(">\n");
+            xml.push_str(&body);
+            xml.push_str("    </testcase>\n");
+        }
+    }
+
+    xml.push_str("  </testsuite>\n</testsuites>\n");
+    xml
+}
+
+/// Writes the report to `path`, substituting `{suite}` with the suite name.
+pub(crate) fn write(path: &Path, suite: &str, reports: &[CaseReport]) -> io::Result<()> {
+    let path = path.to_string_lossy().replace("{suite}", suite);
+    if let Some(parent) = Path::new(&path).parent()
+        && !parent.as_os_str().is_empty()
+    {
+        std::fs::create_dir_all(parent)?;
+    }
+    std::fs::write(path, render(suite, reports))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{escape, render};
+    use crate::report::{CaseReport, Status};
+    use crate::test_support::DEFAULT_CASE;
+    use satchel::test_harness::{SourceLocation, TestCase};
+    use std::time::Duration;
+
+    static CASE: TestCase = TestCase {
+        location: SourceLocation {
+            file: "src/lib.rs",
+            line: 12,
+            column: 8,
+        },
+        ..DEFAULT_CASE
+    };
+
+    fn report(status: Status, message: Option<&str>) -> CaseReport {
+        CaseReport {
+            case: &CASE,
+            status,
+            duration: Duration::from_millis(1500),
+            message: message.map(str::to_string),
+            stdout: None,
+            stderr: None,
+            retried: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn escapes_markup_and_control_characters() {
+        assert_eq!(escape("plain"), "plain");
+        assert_eq!(
+            escape("<a href=\"x\">&'\u{1b}</a>"),
+            "&lt;a href=&quot;x&quot;&gt;&amp;&apos;\u{FFFD}&lt;/a&gt;"
+        );
+    }
+
+    #[test]
+    fn renders_failures_errors_and_skips() {
+        let xml = render(
+            "mycrate",
+            &[
+                report(Status::Passed, None),
+                report(
+                    Status::Failed,
+                    Some("Unexpected panic: boom\n  at src/lib.rs:12:8"),
+                ),
+                report(Status::Skipped(Some("slow".to_string())), None),
+                report(
+                    Status::Error,
+                    Some("Fixture `database` failed to set up: refused"),
+                ),
+            ],
+        );
+        assert!(xml.contains(
+            "<testsuite name=\"mycrate\" tests=\"4\" failures=\"1\" errors=\"1\" skipped=\"1\" time=\"6.000\">"
+        ));
+        assert!(xml.contains(
+            "<testcase name=\"it_works\" classname=\"mycrate::tests\" time=\"1.500\" file=\"src/lib.rs\" line=\"12\"/>"
+        ));
+        assert!(xml.contains(
+            "<failure message=\"Unexpected panic: boom\">Unexpected panic: boom\n  at src/lib.rs:12:8</failure>"
+        ));
+        assert!(xml.contains("<skipped message=\"slow\"/>"));
+        assert!(xml.contains(
+            "<error message=\"Fixture `database` failed to set up: refused\">Fixture `database` failed to set up: refused</error>"
+        ));
+    }
+
+    #[test]
+    fn renders_earlier_attempts_of_retried_cases() {
+        let retried = |status, message| CaseReport {
+            retried: vec!["Unexpected panic: first try".to_string()],
+            ..report(status, message)
+        };
+        let xml = render(
+            "mycrate",
+            &[
+                retried(Status::Passed, None),
+                retried(Status::Failed, Some("Unexpected panic: second try")),
+            ],
+        );
+        assert!(
+            xml.contains("<testsuite name=\"mycrate\" tests=\"2\" failures=\"1\" errors=\"0\"")
+        );
+        assert!(xml.contains(
+            "<flakyFailure message=\"Unexpected panic: first try\"><stackTrace>Unexpected panic: first try</stackTrace></flakyFailure>"
+        ));
+        assert!(xml.contains(
+            "<rerunFailure message=\"Unexpected panic: first try\"><stackTrace>Unexpected panic: first try</stackTrace></rerunFailure>"
+        ));
+    }
+}
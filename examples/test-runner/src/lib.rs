@@ -1,14 +1,54 @@
-use libtest_mimic::{Arguments, Failed, Trial};
-use satchel::test_harness::TestCase;
+mod args;
+mod baseline;
+mod bench;
+mod bisect;
+pub mod c_api;
+mod capture;
+mod depends;
+mod fixtures;
+mod isolation;
+mod json;
+mod junit;
+mod repeat;
+mod report;
+mod retry;
+mod serial;
+mod shard;
+mod shuffle;
+mod tags;
+#[cfg(test)]
+mod test_support;
+
+pub use args::RunnerArgs;
+use bench::BenchResult;
+use capture::{Output, OutputMode};
+use depends::Dependencies;
+use fixtures::FixtureCache;
+use libtest_mimic::{Completion, Failed, FormatSetting, Trial};
+use report::{CaseRun, Recorder, Status};
+use satchel::test_harness::{CaseAttribute, IgnoreCondition, SourceLocation, TestCase};
+use satchel::{Bencher, DefaultExecutor, EntryPoint, Executor, FixtureValues, TestError};
+use serial::SerialGroups;
 use std::cell::Cell;
 use std::panic;
+use std::sync::{Arc, Mutex, Once, mpsc};
+use std::thread;
+use std::time::{Duration, Instant};
+pub use tags::TagExpr;
+
+type SharedExecutor = Arc<dyn Executor + Send + Sync>;
+
+static EXECUTORS: Mutex<Vec<(&'static str, SharedExecutor)>> = Mutex::new(Vec::new());
+
+/// Name of the executor used for `async fn` cases that do not request one explicitly.
+pub const DEFAULT_EXECUTOR: &str = "default";
 
 thread_local! {
-    static ACTIVE_CASE_ATTRIBUTES: Cell<&'static [&'static str]> = Cell::new(&[]);
+    static ACTIVE_CASE_ATTRIBUTES: Cell<&'static [CaseAttribute]> = const { Cell::new(&[]) };
 }
 
 struct CaseAttributesGuard {
-    previous: &'static [&'static str],
+    previous: &'static [CaseAttribute],
 }
 
 impl Drop for CaseAttributesGuard {
@@ -17,14 +57,14 @@ impl Drop for CaseAttributesGuard {
     }
 }
 
-fn push_case_attributes(attributes: &'static [&'static str]) -> CaseAttributesGuard {
+fn push_case_attributes(attributes: &'static [CaseAttribute]) -> CaseAttributesGuard {
     ACTIVE_CASE_ATTRIBUTES.with(|cell| {
         let previous = cell.replace(attributes);
         CaseAttributesGuard { previous }
     })
 }
 
-fn run_with_case_attributes<F, R>(attributes: &'static [&'static str], f: F) -> R
+fn run_with_case_attributes<F, R>(attributes: &'static [CaseAttribute], f: F) -> R
 where
     F: FnOnce() -> R,
 {
@@ -34,153 +74,965 @@ where
     result
 }
 
-fn invoke_test_fn(test_fn: fn(), attributes: &'static [&'static str]) -> std::thread::Result<()> {
-    run_with_case_attributes(attributes, || panic::catch_unwind(|| (test_fn)()))
+fn invoke_test_fn(
+    test_fn: EntryPoint,
+    executor: &dyn Executor,
+    attributes: &'static [CaseAttribute],
+    fixtures: &FixtureValues,
+) -> std::thread::Result<Result<(), TestError>> {
+    run_with_case_attributes(attributes, || {
+        panic::catch_unwind(panic::AssertUnwindSafe(|| {
+            test_fn.run_with_fixtures(executor, fixtures)
+        }))
+    })
+}
+
+/// Like [`invoke_test_fn`], but runs the test on a watchdog thread when a timeout is set.
+/// A test exceeding its timeout fails without waiting for it. Its thread cannot be stopped and
+/// keeps running with its fixtures until the process exits, so [`run_attempt`] runs tests with
+/// a timeout in a child process of their own.
+fn invoke_with_timeout(
+    test_fn: EntryPoint,
+    executor: &SharedExecutor,
+    attributes: &'static [CaseAttribute],
+    fixtures: &FixtureValues,
+    timeout: Option<Duration>,
+    name: &str,
+) -> Result<std::thread::Result<Result<(), TestError>>, Failed> {
+    let Some(timeout) = timeout else {
+        return Ok(invoke_test_fn(test_fn, &**executor, attributes, fixtures));
+    };
+
+    let (sender, receiver) = mpsc::channel();
+    let executor = Arc::clone(executor);
+    let fixtures = fixtures.clone();
+    thread::Builder::new()
+        .name(name.to_string())
+        .spawn(move || {
+            let result = invoke_test_fn(test_fn, &*executor, attributes, &fixtures);
+            // Release the fixtures before reporting, so the caller tears them down.
+            drop(fixtures);
+            let _ = sender.send(result);
+        })
+        .map_err(|e| Failed::from(format!("Failed to spawn test thread: {}", e)))?;
+
+    match receiver.recv_timeout(timeout) {
+        Ok(result) => Ok(result),
+        Err(mpsc::RecvTimeoutError::Timeout) => {
+            Err(Failed::from(format!("Test timed out after {:?}", timeout)))
+        }
+        Err(mpsc::RecvTimeoutError::Disconnected) => Err(Failed::from(
+            "Test thread exited without reporting a result",
+        )),
+    }
+}
+
+/// Registers `executor` under `name` for `async fn` cases annotated with
+/// `#[test(executor = "name")]`, replacing any executor previously registered under that name.
+///
+/// Registering under [`DEFAULT_EXECUTOR`] replaces [`satchel::DefaultExecutor`] for cases that do
+/// not select an executor.
+pub fn register_executor(name: &'static str, executor: impl Executor + Send + Sync + 'static) {
+    let mut executors = EXECUTORS.lock().unwrap();
+    executors.retain(|(registered, _)| *registered != name);
+    executors.push((name, Arc::new(executor)));
 }
 
-pub fn current_case_attributes() -> &'static [&'static str] {
+fn resolve_executor(name: Option<&'static str>) -> Result<SharedExecutor, Failed> {
+    let lookup = name.unwrap_or(DEFAULT_EXECUTOR);
+    let registered = EXECUTORS
+        .lock()
+        .unwrap()
+        .iter()
+        .find(|(registered, _)| *registered == lookup)
+        .map(|(_, executor)| Arc::clone(executor));
+    match (registered, name) {
+        (Some(executor), _) => Ok(executor),
+        (None, None) => Ok(Arc::new(DefaultExecutor)),
+        (None, Some(name)) => Err(Failed::from(format!(
+            "No executor registered under the name \"{}\"",
+            name
+        ))),
+    }
+}
+
+/// Case attributes of the test running on the current thread; look values up with
+/// [`CaseAttribute::find`].
+pub fn current_case_attributes() -> &'static [CaseAttribute] {
     ACTIVE_CASE_ATTRIBUTES.with(|cell| cell.get())
 }
 
-#[cfg(test)]
-mod tests {
-    use super::{current_case_attributes, run_with_case_attributes};
+/// Runs `tests` through libtest-mimic and returns whether none of them failed.
+///
+/// Accepts either libtest-mimic's `Arguments` or [`RunnerArgs`], which add the runner's own
+/// options such as `--junit-xml`.
+pub fn run_tests(
+    tests: impl Iterator<Item = &'static TestCase>,
+    args: impl Into<RunnerArgs>,
+) -> bool {
+    let args = args.into();
+    let start = Instant::now();
+    let mut cases: Vec<&'static TestCase> = tests.collect();
+    if let Some(name) = isolation::requested_case() {
+        // Started as a child process to run a single case, which may live in another library.
+        isolation::run_requested_case(&cases, &name);
+        return true;
+    }
+    if let Some(names) = bisect::requested_sequence() {
+        // Started by `--bisect` to run a sequence of cases.
+        bisect::run_requested_sequence(&cases, &names);
+        return true;
+    }
+    if let Err(e) = depends::check(&cases) {
+        eprintln!("error: {}", e);
+        return false;
+    }
+    let total = cases.len();
+    let mut libtest = args.libtest.clone();
+    let events = if libtest.format == Some(FormatSetting::Json) && !libtest.list {
+        match json::open(libtest.logfile.as_deref()) {
+            Ok(events) => {
+                // The runner writes the events; the human output of libtest-mimic is dropped.
+                libtest.logfile = Some(json::NULL_DEVICE.to_string());
+                Some(events)
+            }
+            Err(e) => {
+                eprintln!("error: failed to open the JSON event stream: {}", e);
+                return false;
+            }
+        }
+    } else {
+        None
+    };
+    let recorder = Recorder::new(events.clone());
+    let output_mode = args.output_mode();
+    // Cases outside the tag expression are left out entirely; the name filter of libtest-mimic
+    // then applies to the rest.
+    if let Some(tags) = &args.tags {
+        cases.retain(|case| tags.matches(case.tags));
+    }
+    if let (Some(index), Some(count)) = (args.shard_index, args.shard_count)
+        && !select_shard(&mut cases, index, count, args.shard_timings.as_deref())
+    {
+        return false;
+    }
+    if let Some(seed) = args.shuffle_seed() {
+        shuffle::shuffle(&mut cases, seed);
+        if !libtest.list {
+            eprintln!(
+                "note: shuffling tests with seed {}; pass --shuffle-seed {} to repeat the order",
+                seed, seed
+            );
+        }
+    }
+    // Dependencies come first, whatever the order so far.
+    depends::order(&mut cases);
+    // Ignore conditions are evaluated once, before any test runs.
+    let ignored: Vec<Option<Option<String>>> =
+        cases.iter().map(|case| evaluate_ignore(case)).collect();
+    let fixtures = Arc::new(FixtureCache::default());
+    let serial = Arc::new(SerialGroups::new(&cases));
+    let dependencies = Arc::new(Dependencies::default());
+    let trials: Vec<Trial> = cases
+        .iter()
+        .zip(&ignored)
+        .map(|(case, ignored)| {
+            create_trial_for_case(
+                case,
+                ignored,
+                &recorder,
+                &args,
+                &fixtures,
+                &serial,
+                &dependencies,
+            )
+        })
+        .collect();
+    let selected: Vec<(&'static TestCase, Option<String>)> = cases
+        .iter()
+        .zip(&trials)
+        .zip(ignored)
+        .filter(|((_, trial), _)| !args.libtest.is_filtered_out(trial))
+        .map(|((case, _), ignored)| (*case, ignored.flatten()))
+        .collect();
+    let scheduled: Vec<&'static TestCase> = cases
+        .iter()
+        .zip(&trials)
+        .filter(|(_, trial)| {
+            !args.libtest.is_filtered_out(trial) && !args.libtest.is_ignored(trial)
+        })
+        .map(|(case, _)| *case)
+        .collect();
+    if let Some(target) = &args.bisect {
+        return bisect::bisect(&scheduled, target, &mut std::io::stdout());
+    }
+    if let Some(plan) = args.repeat_plan() {
+        return repeat_cases(&scheduled, plan, &args, &fixtures, &serial);
+    }
+    fixtures.schedule(&scheduled);
+    dependencies.schedule(&scheduled);
+    if let Some(events) = &events {
+        events.suite_started(selected.len());
+    }
 
-    #[test]
-    fn case_attributes_reset_to_previous() {
-        assert!(current_case_attributes().is_empty());
-        run_with_case_attributes(&["one", "--two=2"], || {
-            assert_eq!(current_case_attributes(), &["one", "--two=2"]);
-        });
-        assert!(current_case_attributes().is_empty());
+    let conclusion = libtest_mimic::run(&libtest, trials);
+    if args.libtest.list {
+        return !conclusion.has_failed();
     }
+
+    let torn_down = fixtures.finish();
+    let reports = recorder.finish(&selected);
+    // Text that would interleave with the event stream goes to stderr instead.
+    let mut out: Box<dyn std::io::Write> = match events {
+        Some(_) => Box::new(std::io::stderr()),
+        None => Box::new(std::io::stdout()),
+    };
+    if events.is_none() {
+        // Best effort, like the summary libtest-mimic printed before. The events carry the
+        // rates and the output themselves.
+        if output_mode == OutputMode::Show {
+            let _ = capture::write_successes(&reports, &mut out);
+        }
+        let _ = bench::write_throughput(&reports, &mut out);
+        let _ = retry::write_flaky(&reports, &mut out);
+    }
+    let reports_written = write_reports(&args, &reports);
+    let baselines_ok = handle_baselines(&args, &reports, &mut out);
+    let passed = !conclusion.has_failed() && torn_down && reports_written && baselines_ok;
+    if let Some(events) = &events {
+        events.suite_finished(&reports, total - selected.len(), passed, start.elapsed());
+    }
+    passed
 }
 
-pub fn run_tests(tests: impl Iterator<Item = &'static TestCase>, args: Arguments) -> bool {
-    let trials: Vec<Trial> = tests.map(create_trial_for_case).collect();
-    !libtest_mimic::run(&args, trials).has_failed()
+/// Runs `cases` over and over for `--repeat` and `--until-fail`, without retries, and returns
+/// whether they all passed. Module-scoped fixtures live until the end of the run.
+fn repeat_cases(
+    cases: &[&'static TestCase],
+    plan: repeat::Plan,
+    args: &RunnerArgs,
+    fixtures: &FixtureCache,
+    serial: &SerialGroups,
+) -> bool {
+    let output_mode = args.output_mode();
+    let run = |case| {
+        let _serial = serial.lock(case);
+        match run_case(case, true, args.isolates(), output_mode, 0, fixtures).result {
+            Ok(_) => repeat::Outcome::Passed,
+            Err(CaseError::Skipped(_)) => repeat::Outcome::Skipped,
+            Err(CaseError::Failed(failed) | CaseError::Fixture(failed)) => {
+                repeat::Outcome::Failed(failed.message().unwrap_or_default().to_string())
+            }
+        }
+    };
+    let passed = match repeat::run(cases, plan, run, &mut std::io::stdout()) {
+        Ok(passed) => passed,
+        Err(e) => {
+            eprintln!("error: failed to write the repeat summary: {}", e);
+            false
+        }
+    };
+    fixtures.finish() && passed
 }
 
-fn format_test_name(case: &TestCase) -> String {
-    let base_name = format!("{}::{}", case.module_path, case.name);
-    case.ignore
-        .as_ref()
-        .and_then(|info| info.reason)
-        .map(|reason| format!("{} (ignored: {})", base_name, reason))
-        .unwrap_or(base_name)
+/// Keeps the cases of shard `index` of `count`, balanced by the timing file if one is given.
+/// Returns `false` if the shard is out of range or the timing file cannot be read.
+fn select_shard(
+    cases: &mut Vec<&'static TestCase>,
+    index: usize,
+    count: usize,
+    timings: Option<&std::path::Path>,
+) -> bool {
+    let shard = match shard::Shard::new(index, count) {
+        Ok(shard) => shard,
+        Err(e) => {
+            eprintln!("error: {}", e);
+            return false;
+        }
+    };
+    let timings = match timings.map(shard::load_timings).transpose() {
+        Ok(timings) => timings,
+        Err(e) => {
+            eprintln!(
+                "error: failed to read test timings from {}: {}",
+                timings.unwrap().display(),
+                e
+            );
+            return false;
+        }
+    };
+    shard::select(cases, shard, timings.as_ref());
+    true
 }
 
-fn apply_ignore_flag(trial: Trial, case: &TestCase) -> Trial {
-    if case.ignore.is_some() {
-        trial.with_ignored_flag(true)
-    } else {
-        trial
+/// Compares with `--baseline` and then saves to `--save-baseline`, so both may name the same
+/// baseline. Returns `false` on a regression or if a baseline cannot be read or written.
+fn handle_baselines(
+    args: &RunnerArgs,
+    reports: &[report::CaseReport],
+    mut out: &mut dyn std::io::Write,
+) -> bool {
+    let mut ok = true;
+    if let Some(name) = &args.baseline {
+        let path = baseline::path(&args.baseline_dir, name);
+        match baseline::compare(&path, reports, args.regression_threshold, &mut out) {
+            Ok(true) => {}
+            Ok(false) => {
+                eprintln!(
+                    "error: benchmarks regressed by more than {}% against baseline `{}`",
+                    args.regression_threshold, name
+                );
+                ok = false;
+            }
+            Err(e) => {
+                eprintln!(
+                    "error: failed to read baseline `{}` from {}: {}",
+                    name,
+                    path.display(),
+                    e
+                );
+                ok = false;
+            }
+        }
+    }
+    if let Some(name) = &args.save_baseline {
+        let path = baseline::path(&args.baseline_dir, name);
+        if let Err(e) = baseline::save(&path, reports) {
+            eprintln!(
+                "error: failed to save baseline `{}` to {}: {}",
+                name,
+                path.display(),
+                e
+            );
+            ok = false;
+        }
+    }
+    ok
+}
+
+fn suite_name(cases: &[report::CaseReport]) -> &'static str {
+    cases
+        .first()
+        .map(|report| satchel::extract_crate_name(report.case.module_path))
+        .unwrap_or("satchel")
+}
+
+fn write_reports(args: &RunnerArgs, reports: &[report::CaseReport]) -> bool {
+    let mut written = true;
+    if let Some(path) = &args.save_timings
+        && let Err(e) = shard::save_timings(path, reports)
+    {
+        eprintln!(
+            "error: failed to save test timings to {}: {}",
+            path.display(),
+            e
+        );
+        written = false;
+    }
+    if let Some(path) = &args.junit_xml
+        && let Err(e) = junit::write(path, suite_name(reports), reports)
+    {
+        eprintln!(
+            "error: failed to write JUnit report to {}: {}",
+            path.display(),
+            e
+        );
+        written = false;
+    }
+    written
+}
+
+/// `module_path::name`, which identifies a case across libraries.
+pub(crate) fn full_name(case: &TestCase) -> String {
+    format!("{}::{}", case.module_path, case.name)
+}
+
+/// Whether the `#[ignore]` of `case` applies in this environment, with the reason to report.
+/// Returns `None` if the case runs; a met condition without a reason is described instead.
+pub(crate) fn evaluate_ignore(case: &TestCase) -> Option<Option<String>> {
+    let ignore = case.ignore.as_ref()?;
+    let condition = match ignore.condition {
+        None => return Some(ignore.reason.map(str::to_string)),
+        Some(IgnoreCondition::IfEnv(name)) => std::env::var_os(name)
+            .is_some()
+            .then(|| format!("{} is set", name)),
+        Some(IgnoreCondition::UnlessEnv(name)) => std::env::var_os(name)
+            .is_none()
+            .then(|| format!("{} is not set", name)),
+        Some(IgnoreCondition::If { name, predicate }) => {
+            predicate().then(|| format!("{}() returned true", name))
+        }
+    }?;
+    Some(Some(ignore.reason.map_or(condition, str::to_string)))
+}
+
+fn format_test_name(case: &TestCase, ignored: &Option<Option<String>>) -> String {
+    let base_name = full_name(case);
+    match ignored {
+        Some(Some(reason)) => format!("{} (ignored: {})", base_name, reason),
+        _ => base_name,
     }
 }
 
 fn handle_unit_test(
-    result: std::thread::Result<()>,
+    result: std::thread::Result<Result<(), TestError>>,
     should_panic: Option<satchel::ShouldPanic>,
 ) -> Result<(), Failed> {
     match (should_panic, result) {
         (Some(panic), Err(e)) => handle_expected_panic(e, panic),
         (Some(_), Ok(_)) => Err(Failed::from("Expected panic did not occur")),
-        (None, Ok(_)) => Ok(()),
-        (None, Err(e)) => Err(Failed::from(format!("Unexpected panic: {:?}", e))),
+        (None, Ok(Ok(()))) => Ok(()),
+        (None, Ok(Err(error))) => Err(Failed::from(format!("Test returned an error: {}", error))),
+        (None, Err(e)) => Err(Failed::from(format!(
+            "Unexpected panic: {}",
+            describe_panic(&*e)
+        ))),
+    }
+}
+
+fn panic_message(payload: &(dyn std::any::Any + Send)) -> Option<&str> {
+    if let Some(msg) = payload.downcast_ref::<&str>() {
+        Some(msg)
+    } else {
+        payload.downcast_ref::<String>().map(String::as_str)
     }
 }
 
+fn describe_panic(payload: &(dyn std::any::Any + Send)) -> String {
+    panic_message(payload)
+        .unwrap_or("<non-string panic payload>")
+        .to_string()
+}
+
 fn handle_expected_panic(
     e: Box<dyn std::any::Any + Send>,
     panic: satchel::ShouldPanic,
 ) -> Result<(), Failed> {
-    let panic_msg = if let Some(msg) = e.downcast_ref::<&str>() {
-        *msg
-    } else if let Some(msg) = e.downcast_ref::<String>() {
-        msg.as_str()
-    } else {
+    let Some(panic_msg) = panic_message(&*e) else {
         return Err(Failed::from("Test panicked with a non-string message"));
     };
 
-    if let Some(expected_msg) = panic.expected {
-        if !panic_msg.contains(expected_msg) {
-            return Err(Failed::from(format!(
-                "Panic message did not contain expected string.\nExpected substring: {}\n      Found string: {}",
-                expected_msg, panic_msg
-            )));
-        }
+    if let Some(expected_msg) = panic.expected
+        && !panic_msg.contains(expected_msg)
+    {
+        return Err(Failed::from(format!(
+            "Panic message did not contain expected string.\nExpected substring: {}\n      Found string: {}",
+            expected_msg, panic_msg
+        )));
     }
     Ok(())
 }
 
 fn run_benchmark(
-    test_fn: fn(),
-    case_attributes: &'static [&'static str],
-) -> Result<Option<libtest_mimic::Measurement>, Failed> {
-    use std::time::Instant;
-    const N: u64 = 1000;
-    let mut times = Vec::with_capacity(N as usize);
-
-    run_with_case_attributes(case_attributes, || {
-        for _ in 0..N {
-            let start = Instant::now();
-            test_fn();
-            let elapsed = start.elapsed().as_nanos() as f64;
-            times.push(elapsed);
+    test_fn: EntryPoint,
+    executor: &dyn Executor,
+    case_attributes: &'static [CaseAttribute],
+    fixtures: &FixtureValues,
+    name: &str,
+) -> Result<Option<BenchResult>, Failed> {
+    let (summary, throughput) = run_with_case_attributes(case_attributes, || match test_fn {
+        EntryPoint::Bench(bench_fn) => {
+            let mut driver = bench::Driver::default();
+            let mut bencher = Bencher::new(&mut driver);
+            bench_fn(&mut bencher)?;
+            let throughput = bencher.throughput();
+            let summary = driver.summary.ok_or_else(|| TestError {
+                debug: "the benchmark did not call Bencher::iter or Bencher::iter_batched"
+                    .to_string(),
+            })?;
+            Ok((summary, throughput))
         }
+        _ => bench::measure(bench::timed(|| {
+            test_fn.run_with_fixtures(executor, fixtures)
+        }))
+        .map(|summary| (summary, None)),
+    })
+    .map_err(|error| Failed::from(format!("Bench returned an error: {}", error)))?;
+    if let Some(warning) = summary.noise_warning() {
+        eprintln!("note: {}: {}", name, warning);
+    }
+    Ok(Some(BenchResult {
+        measurement: summary.measurement(),
+        throughput,
+    }))
+}
+
+fn with_location(failed: Failed, location: SourceLocation) -> Failed {
+    match failed.message() {
+        Some(msg) => Failed::from(format!("{}\n  at {}", msg, location)),
+        None => Failed::from(format!("at {}", location)),
+    }
+}
+
+/// Why a case did not pass.
+#[derive(Debug)]
+pub(crate) enum CaseError {
+    /// The test failed.
+    Failed(Failed),
+    /// A fixture requested by the test failed to set up or tear down.
+    Fixture(Failed),
+    /// The test called `satchel::skip!`, with the reason if it gave one.
+    Skipped(Option<String>),
+}
+
+impl From<Failed> for CaseError {
+    fn from(failed: Failed) -> Self {
+        CaseError::Failed(failed)
+    }
+}
+
+/// Keeps the panic hook quiet about `satchel::skip!`, which unwinds the test on purpose.
+fn silence_skip_panics() {
+    static INSTALL: Once = Once::new();
+    INSTALL.call_once(|| {
+        let previous = panic::take_hook();
+        panic::set_hook(Box::new(move |info| {
+            if info
+                .payload_as_str()
+                .and_then(satchel::skip_reason)
+                .is_none()
+            {
+                previous(info);
+            }
+        }));
     });
+}
 
-    let avg = times.iter().sum::<f64>() / N as f64;
-    let variance = times.iter().map(|&x| (x - avg).powi(2)).sum::<f64>() / N as f64;
-    Ok(Some(libtest_mimic::Measurement {
-        avg: avg.round() as u64,
-        variance: variance.round() as u64,
-    }))
+/// Runs a single case to completion and reports its outcome; shared by the libtest-mimic
+/// trials and the C API. `test_mode` runs benchmarks once instead of measuring them.
+pub(crate) fn execute_case(
+    case: &'static TestCase,
+    test_mode: bool,
+    fixtures: &FixtureCache,
+) -> Result<Option<BenchResult>, CaseError> {
+    silence_skip_panics();
+    let values = match fixtures.acquire(case) {
+        Ok(values) => values,
+        Err(failed) => {
+            let _ = fixtures.release(case, None);
+            return Err(CaseError::Fixture(failed));
+        }
+    };
+    let result = invoke_case(case, test_mode, &values);
+    let released = fixtures.release(case, Some(values));
+    let value = result?;
+    released.map_err(CaseError::Fixture)?;
+    Ok(value)
 }
 
-fn create_trial_for_case(case: &'static TestCase) -> Trial {
-    let full_name = format_test_name(case);
-    let kind_str = format!("{:?}", case.kind);
+fn invoke_case(
+    case: &'static TestCase,
+    test_mode: bool,
+    fixtures: &FixtureValues,
+) -> Result<Option<BenchResult>, CaseError> {
+    let executor = resolve_executor(case.executor)?;
+    // `satchel::skip!` unwinds the test with a marker panic, which ends it as skipped
+    // regardless of `#[should_panic]` or retries.
+    let invoke = || {
+        let result = invoke_with_timeout(
+            case.test_fn,
+            &executor,
+            case.case_attributes,
+            fixtures,
+            case.timeout,
+            case.name,
+        )?;
+        if let Err(payload) = &result
+            && let Some(reason) = panic_message(&**payload).and_then(satchel::skip_reason)
+        {
+            return Err(CaseError::Skipped(
+                (!reason.is_empty()).then(|| reason.to_string()),
+            ));
+        }
+        Ok(result)
+    };
 
     match case.kind {
         satchel::TestKind::Unit => {
-            let should_panic = case.should_panic.clone();
-            let case_attributes = case.case_attributes;
-            let retry_on_failure = should_panic.is_none()
-                && case_attributes
-                    .iter()
-                    .any(|attr| *attr == "retry_on_failure");
-            let test_fn = case.test_fn;
-            let trial = Trial::test(full_name, move || {
-                let mut result = invoke_test_fn(test_fn, case_attributes);
-                if retry_on_failure && result.is_err() {
-                    result = invoke_test_fn(test_fn, case_attributes);
-                }
-                handle_unit_test(result, should_panic.clone())
+            handle_unit_test(invoke()?, case.should_panic.clone()).map(|()| None)
+        }
+        satchel::TestKind::Benchmark => match (test_mode, invoke()?) {
+            (_, Ok(Err(error))) => Err(Failed::from(format!("Bench returned an error: {}", error))),
+            (true, Ok(_)) => Ok(None),
+            (true, Err(e)) => Err(Failed::from(format!(
+                "Bench panicked in test_mode: {}",
+                describe_panic(&*e)
+            ))),
+            (false, Err(e)) => Err(Failed::from(format!(
+                "Bench panicked in bench mode: {}",
+                describe_panic(&*e)
+            ))),
+            (false, Ok(_)) => run_benchmark(
+                case.test_fn,
+                &*executor,
+                case.case_attributes,
+                fixtures,
+                &full_name(case),
+            ),
+        },
+    }
+    .map_err(|failed| CaseError::Failed(with_location(failed, case.location)))
+}
+
+/// Runs `case` until it passes or has failed `retries` more times, and returns the outcome of
+/// the last attempt with the failures of the ones before it.
+fn run_case(
+    case: &'static TestCase,
+    test_mode: bool,
+    isolate: bool,
+    output_mode: OutputMode,
+    retries: u32,
+    fixtures: &FixtureCache,
+) -> CaseRun<Option<BenchResult>> {
+    let mut retried = Vec::new();
+    loop {
+        let may_retry = retried.len() < retries as usize;
+        if may_retry {
+            fixtures.expect_retry(case);
+        }
+        let (result, output) = run_attempt(case, test_mode, isolate, output_mode, fixtures);
+        if may_retry && !matches!(result, Err(CaseError::Failed(_))) {
+            let _ = fixtures.release(case, None);
+        }
+        let result = match result {
+            Err(CaseError::Failed(failed)) if may_retry => {
+                retried.push(failed.message().unwrap_or_default().to_string());
+                continue;
+            }
+            Err(CaseError::Failed(failed)) if !retried.is_empty() => {
+                let message = failed.message().unwrap_or_default();
+                let attempts = retried.len() + 1;
+                Err(CaseError::Failed(Failed::from(retry::note_attempts(
+                    message, attempts,
+                ))))
+            }
+            result => result,
+        };
+        return CaseRun {
+            result,
+            output,
+            retried,
+        };
+    }
+}
+
+/// Runs `case` once in this process, or in a child process when tests are isolated or it has a
+/// timeout, and returns its outcome with the output it captured. A failure carries the captured
+/// output in its message.
+fn run_attempt(
+    case: &'static TestCase,
+    test_mode: bool,
+    isolate: bool,
+    output_mode: OutputMode,
+    fixtures: &FixtureCache,
+) -> (Result<Option<BenchResult>, CaseError>, Option<Output>) {
+    let (result, output) = if isolate || case.timeout.is_some() {
+        let run = isolation::run_isolated(case, test_mode, output_mode.captures());
+        // The child set up its own fixtures; this process counts the case as done with them.
+        fixtures.skip(case);
+        run
+    } else if output_mode.captures() && test_mode {
+        // Measured benchmarks are left alone, as they repeat their output for every iteration.
+        let (result, output) = capture::capture(|| execute_case(case, test_mode, fixtures));
+        (result, Some(output))
+    } else {
+        (execute_case(case, test_mode, fixtures), None)
+    };
+    let Some(output) = output else {
+        return (result, None);
+    };
+    let result = result.map_err(|error| match error {
+        CaseError::Failed(failed) => CaseError::Failed(output.append_to(failed)),
+        CaseError::Fixture(failed) => CaseError::Fixture(output.append_to(failed)),
+        CaseError::Skipped(reason) => CaseError::Skipped(reason),
+    });
+    (result, Some(output))
+}
+
+/// Runs `case` through `run` unless `blocked` holds why one of its dependencies did not pass,
+/// and records how it ended for the cases depending on it.
+fn run_after_dependencies<T>(
+    case: &'static TestCase,
+    blocked: Result<(), String>,
+    dependencies: &Dependencies,
+    fixtures: &FixtureCache,
+    run: impl FnOnce() -> CaseRun<T>,
+) -> CaseRun<T> {
+    let run = match blocked {
+        Ok(()) => run(),
+        Err(reason) => {
+            fixtures.skip(case);
+            CaseRun {
+                result: Err(CaseError::Skipped(Some(reason))),
+                output: None,
+                retried: Vec::new(),
+            }
+        }
+    };
+    let outcome = match &run.result {
+        Ok(_) => depends::Outcome::Passed,
+        Err(CaseError::Skipped(_)) => depends::Outcome::Skipped,
+        Err(CaseError::Failed(_) | CaseError::Fixture(_)) => depends::Outcome::Failed,
+    };
+    dependencies.record(case, outcome);
+    run
+}
+
+fn create_trial_for_case(
+    case: &'static TestCase,
+    ignored: &Option<Option<String>>,
+    recorder: &Recorder,
+    args: &RunnerArgs,
+    fixtures: &Arc<FixtureCache>,
+    serial: &Arc<SerialGroups>,
+    dependencies: &Arc<Dependencies>,
+) -> Trial {
+    let test_name = format_test_name(case, ignored);
+    let recorder = recorder.clone();
+    let kind_str = format!("{:?}", case.kind);
+    let isolate = args.isolates();
+    let output_mode = args.output_mode();
+    let retries = retry::retries(case, args.retries);
+    let fixtures = Arc::clone(fixtures);
+    let serial = Arc::clone(serial);
+    let dependencies = Arc::clone(dependencies);
+
+    // The wait for the dependencies and the serial group of the case is not part of its duration.
+    let trial = match case.kind {
+        satchel::TestKind::Unit => Trial::ignorable_test(test_name, move || {
+            let blocked = dependencies.wait(case);
+            let _serial = blocked.is_ok().then(|| serial.lock(case));
+            let completed = recorder.measure(
+                case,
+                || {
+                    let run =
+                        run_after_dependencies(case, blocked, &dependencies, &fixtures, || {
+                            run_case(case, true, isolate, output_mode, retries, &fixtures)
+                        });
+                    CaseRun {
+                        result: run.result.map(|_| ()),
+                        output: run.output,
+                        retried: run.retried,
+                    }
+                },
+                |_| Status::Passed,
+            )?;
+            Ok(match completed {
+                Ok(()) => Completion::Completed,
+                Err(Some(reason)) => Completion::ignored_with(reason),
+                Err(None) => Completion::ignored(),
             })
-            .with_kind(kind_str);
-            apply_ignore_flag(trial, case)
-        }
-        satchel::TestKind::Benchmark => {
-            let case_attributes = case.case_attributes;
-            let test_fn = case.test_fn;
-            let trial = Trial::bench(full_name, move |test_mode| {
-                let result = invoke_test_fn(test_fn, case_attributes);
-                match (test_mode, result) {
-                    (true, Ok(_)) => Ok(None),
-                    (true, Err(e)) => Err(Failed::from(format!(
-                        "Bench panicked in test_mode: {:?}",
-                        e
-                    ))),
-                    (false, Err(e)) => Err(Failed::from(format!(
-                        "Bench panicked in bench mode: {:?}",
-                        e
-                    ))),
-                    (false, Ok(_)) => run_benchmark(test_fn, case_attributes),
-                }
+        }),
+        satchel::TestKind::Benchmark => Trial::bench(test_name, move |test_mode| {
+            let blocked = dependencies.wait(case);
+            let _serial = blocked.is_ok().then(|| serial.lock(case));
+            recorder
+                .measure(
+                    case,
+                    || {
+                        run_after_dependencies(case, blocked, &dependencies, &fixtures, || {
+                            run_case(case, test_mode, isolate, output_mode, retries, &fixtures)
+                        })
+                    },
+                    |result| match result {
+                        Some(result) => Status::Measured(*result),
+                        None => Status::Passed,
+                    },
+                )
+                // libtest-mimic cannot ignore a benchmark while it runs; only the reports record
+                // one that skipped itself as skipped.
+                .map(|result| result.ok().flatten().map(|result| result.measurement))
+        }),
+    };
+    trial
+        .with_kind(kind_str)
+        .with_ignored_flag(ignored.is_some())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        Recorder, RunnerArgs, SerialGroups, SharedExecutor, create_trial_for_case,
+        current_case_attributes, handle_unit_test, invoke_with_timeout, resolve_executor,
+        run_tests, run_with_case_attributes,
+    };
+    use crate::test_support::DEFAULT_CASE;
+    use satchel::test_harness::{Ignore, IgnoreCondition, TestCase};
+    use satchel::{AttributeValue, CaseAttribute, EntryPoint, FixtureValues, TestError};
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    #[test]
+    fn returned_error_is_reported_with_debug_text() {
+        let result = Ok(Err(TestError {
+            debug: "ParseIntError { kind: InvalidDigit }".to_string(),
+        }));
+        let failed = handle_unit_test(result, None).unwrap_err();
+        assert_eq!(
+            failed.message(),
+            Some("Test returned an error: ParseIntError { kind: InvalidDigit }")
+        );
+    }
+
+    #[test]
+    fn unknown_executor_name_fails_the_case() {
+        let failed = resolve_executor(Some("missing")).err().unwrap();
+        assert_eq!(
+            failed.message(),
+            Some("No executor registered under the name \"missing\"")
+        );
+        assert!(resolve_executor(None).is_ok());
+    }
+
+    #[test]
+    fn exceeding_the_timeout_is_reported_as_timeout() {
+        fn hangs() {
+            std::thread::sleep(Duration::from_secs(5));
+        }
+        let executor: SharedExecutor = Arc::new(satchel::DefaultExecutor);
+        let failed = invoke_with_timeout(
+            EntryPoint::Plain(hangs),
+            &executor,
+            &[],
+            &FixtureValues::default(),
+            Some(Duration::from_millis(20)),
+            "hangs",
+        )
+        .err()
+        .unwrap();
+        assert_eq!(failed.message(), Some("Test timed out after 20ms"));
+    }
+
+    #[test]
+    fn finishing_within_the_timeout_reports_the_result() {
+        fn quick() {}
+        let executor: SharedExecutor = Arc::new(satchel::DefaultExecutor);
+        let result = invoke_with_timeout(
+            EntryPoint::Plain(quick),
+            &executor,
+            &[],
+            &FixtureValues::default(),
+            Some(Duration::from_secs(5)),
+            "quick",
+        );
+        assert!(matches!(result, Ok(Ok(Ok(())))));
+    }
+
+    #[test]
+    fn case_attributes_reset_to_previous() {
+        static ATTRIBUTES: [CaseAttribute; 2] = [
+            CaseAttribute {
+                key: "one",
+                value: AttributeValue::Flag,
+            },
+            CaseAttribute {
+                key: "two",
+                value: AttributeValue::Int(2),
+            },
+        ];
+        assert!(current_case_attributes().is_empty());
+        run_with_case_attributes(&ATTRIBUTES, || {
+            assert_eq!(current_case_attributes(), &ATTRIBUTES);
+            assert_eq!(
+                CaseAttribute::find(current_case_attributes(), "two"),
+                Some(&AttributeValue::Int(2))
+            );
+        });
+        assert!(current_case_attributes().is_empty());
+    }
+
+    #[test]
+    fn tag_expression_selects_cases() {
+        static CASES: [TestCase; 3] = [
+            TestCase {
+                name: "quick",
+                tags: &["smoke"],
+                ..DEFAULT_CASE
+            },
+            TestCase {
+                name: "slow",
+                test_fn: EntryPoint::Plain(|| panic!("deselected by --tags")),
+                tags: &["smoke", "slow"],
+                ..DEFAULT_CASE
+            },
+            TestCase {
+                name: "untagged",
+                test_fn: EntryPoint::Plain(|| panic!("deselected by --tags")),
+                ..DEFAULT_CASE
+            },
+        ];
+
+        let args = RunnerArgs::parse_from(["host", "--tags", "smoke & !slow", "--quiet"]);
+        assert!(run_tests(CASES.iter(), args));
+        let args = RunnerArgs::parse_from(["host", "--tags", "slow", "--quiet"]);
+        assert!(!run_tests(CASES.iter(), args));
+    }
+
+    #[test]
+    fn skips_and_met_ignore_conditions_are_reported_as_skipped() {
+        const SKIPPING: TestCase = TestCase {
+            module_path: "skipping::tests",
+            ..DEFAULT_CASE
+        };
+        const fn ignore_if(predicate: fn() -> bool) -> Option<Ignore> {
+            Some(Ignore {
+                reason: None,
+                condition: Some(IgnoreCondition::If {
+                    name: "predicate",
+                    predicate,
+                }),
             })
-            .with_kind(kind_str);
-            apply_ignore_flag(trial, case)
         }
+        static CASES: [TestCase; 3] = [
+            TestCase {
+                name: "skips",
+                test_fn: EntryPoint::Plain(|| satchel::skip!("no {}", "device")),
+                ..SKIPPING
+            },
+            TestCase {
+                name: "holds",
+                test_fn: EntryPoint::Plain(|| panic!("ignored")),
+                ignore: ignore_if(|| true),
+                ..SKIPPING
+            },
+            TestCase {
+                name: "does_not_hold",
+                ignore: ignore_if(|| false),
+                ..SKIPPING
+            },
+        ];
+
+        let path = std::env::temp_dir().join(format!("satchel-skips-{}.xml", std::process::id()));
+        let args = RunnerArgs::parse_from([
+            "host".as_ref(),
+            "--quiet".as_ref(),
+            "--junit-xml".as_ref(),
+            path.as_os_str(),
+        ]);
+        assert!(run_tests(CASES.iter(), args));
+        let xml = std::fs::read_to_string(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        assert!(xml.contains("skipped=\"2\""), "{}", xml);
+        assert!(xml.contains("<skipped message=\"no device\"/>"), "{}", xml);
+        assert!(
+            xml.contains("<skipped message=\"predicate() returned true\"/>"),
+            "{}",
+            xml
+        );
+
+        // libtest-mimic counts the case that skipped itself as ignored, not as passed.
+        let args = RunnerArgs::parse_from(["host", "--quiet"]);
+        let trial = create_trial_for_case(
+            &CASES[0],
+            &None,
+            &Recorder::default(),
+            &args,
+            &Arc::default(),
+            &Arc::new(SerialGroups::new(&[])),
+            &Arc::default(),
+        );
+        let conclusion = libtest_mimic::run(&args.libtest, vec![trial]);
+        assert_eq!((conclusion.num_passed, conclusion.num_ignored), (0, 1));
     }
 }
@@ -0,0 +1,280 @@
+//! Stress runs for `--repeat N` and `--until-fail`: the selected cases run over and over to
+//! reproduce failures that only show up now and then.
+//!
+//! Every iteration runs each case once. The runs are shared out among `--test-threads` threads
+//! in iteration order, so with several threads a case may run concurrently with itself. As
+//! usual, with several threads and captured output every run gets a process of its own. Failures
+//! are printed as they happen, and the run ends with the first failure and the pass rate of every
+//! case:
+//!
+//! ```text
+//! first failure: iteration 37 of somelib::tests::reconnects
+//! Unexpected panic: connection reset
+//!   at src/lib.rs:42:8
+//!
+//! pass rate:
+//!   somelib::tests::reconnects     97/100   97.0%  |################### |
+//!   somelib::tests::it_works      100/100  100.0%  |####################|
+//! ```
+
+use crate::full_name;
+use satchel::test_harness::TestCase;
+use std::io::{self, Write};
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+
+/// Width of the bars of the pass rate histogram.
+const BAR_WIDTH: usize = 20;
+
+/// How long to keep repeating.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct Plan {
+    /// Number of iterations; `None` repeats until a case fails.
+    pub iterations: Option<u64>,
+    /// Stop starting runs once a case has failed.
+    pub until_fail: bool,
+    pub threads: usize,
+}
+
+/// Outcome of one run of a case.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) enum Outcome {
+    Passed,
+    /// The failure message.
+    Failed(String),
+    /// The case skipped itself at run time.
+    Skipped,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+struct Tally {
+    passed: u64,
+    failed: u64,
+    skipped: u64,
+}
+
+/// The earliest failure seen so far.
+struct FirstFailure {
+    iteration: u64,
+    case: usize,
+    message: String,
+}
+
+fn plural(count: u64, noun: &str) -> String {
+    match count {
+        1 => format!("1 {}", noun),
+        n => format!("{} {}s", n, noun),
+    }
+}
+
+/// Runs `cases` as `plan` says, through `run`, and writes the failures and the summary to
+/// `out`. Returns whether every run passed or skipped.
+pub(crate) fn run(
+    cases: &[&'static TestCase],
+    plan: Plan,
+    run: impl Fn(&'static TestCase) -> Outcome + Sync,
+    out: &mut (impl Write + Send),
+) -> io::Result<bool> {
+    let count = cases.len() as u64;
+    let tests = plural(count, "test");
+    let threads = plural(plan.threads as u64, "thread");
+    match plan.iterations {
+        Some(iterations) => writeln!(
+            out,
+            "repeat: running {} {} times on {}{}",
+            tests,
+            iterations,
+            threads,
+            if plan.until_fail {
+                ", stopping at the first failure"
+            } else {
+                ""
+            }
+        )?,
+        None => writeln!(
+            out,
+            "repeat: running {} on {} until one fails",
+            tests, threads
+        )?,
+    }
+    let total = plan.iterations.map(|iterations| iterations * count);
+    if count == 0 || total == Some(0) {
+        return Ok(true);
+    }
+
+    let next = AtomicU64::new(0);
+    let stop = AtomicBool::new(false);
+    let tallies = Mutex::new(vec![Tally::default(); cases.len()]);
+    let first = Mutex::new(None::<FirstFailure>);
+    let out = Mutex::new(out);
+    std::thread::scope(|scope| {
+        for _ in 0..plan.threads.max(1) {
+            scope.spawn(|| {
+                while !stop.load(Ordering::Relaxed) {
+                    let index = next.fetch_add(1, Ordering::Relaxed);
+                    if total.is_some_and(|total| index >= total) {
+                        break;
+                    }
+                    let iteration = index / count + 1;
+                    let case = (index % count) as usize;
+                    let outcome = run(cases[case]);
+                    let mut tallies = tallies.lock().unwrap();
+                    let tally = &mut tallies[case];
+                    match outcome {
+                        Outcome::Passed => tally.passed += 1,
+                        Outcome::Skipped => tally.skipped += 1,
+                        Outcome::Failed(message) => {
+                            tally.failed += 1;
+                            drop(tallies);
+                            let _ = writeln!(
+                                out.lock().unwrap(),
+                                "iteration {}: {} failed",
+                                iteration,
+                                full_name(cases[case])
+                            );
+                            let mut first = first.lock().unwrap();
+                            if first
+                                .as_ref()
+                                .is_none_or(|first| iteration < first.iteration)
+                            {
+                                *first = Some(FirstFailure {
+                                    iteration,
+                                    case,
+                                    message,
+                                });
+                            }
+                            if plan.until_fail {
+                                stop.store(true, Ordering::Relaxed);
+                            }
+                        }
+                    }
+                }
+            });
+        }
+    });
+
+    let out = out.into_inner().unwrap();
+    let first = first.into_inner().unwrap();
+    if let Some(first) = &first {
+        writeln!(
+            out,
+            "\nfirst failure: iteration {} of {}\n{}",
+            first.iteration,
+            full_name(cases[first.case]),
+            first.message.trim_end()
+        )?;
+    }
+    write_histogram(cases, &tallies.into_inner().unwrap(), out)?;
+    Ok(first.is_none())
+}
+
+/// Writes the pass rate of every case that ran, lowest first, as a bar.
+fn write_histogram(
+    cases: &[&'static TestCase],
+    tallies: &[Tally],
+    out: &mut impl Write,
+) -> io::Result<()> {
+    let mut rows: Vec<(String, Tally)> = cases
+        .iter()
+        .zip(tallies)
+        .filter(|(_, tally)| tally.passed + tally.failed > 0)
+        .map(|(case, tally)| (full_name(case), *tally))
+        .collect();
+    let rate = |tally: &Tally| tally.passed as f64 / (tally.passed + tally.failed) as f64;
+    rows.sort_by(|(a_name, a), (b_name, b)| {
+        rate(a).total_cmp(&rate(b)).then_with(|| a_name.cmp(b_name))
+    });
+    writeln!(out, "\npass rate:")?;
+    let width = rows.iter().map(|(name, _)| name.len()).max().unwrap_or(0);
+    for (name, tally) in &rows {
+        let runs = tally.passed + tally.failed;
+        let filled = (rate(tally) * BAR_WIDTH as f64).floor() as usize;
+        writeln!(
+            out,
+            "  {:<width$}  {:>9}  {:>6.1}%  |{}{}|",
+            name,
+            format!("{}/{}", tally.passed, runs),
+            rate(tally) * 100.0,
+            "#".repeat(filled),
+            " ".repeat(BAR_WIDTH - filled),
+            width = width
+        )?;
+    }
+    let skipped: u64 = tallies.iter().map(|tally| tally.skipped).sum();
+    if skipped > 0 {
+        writeln!(out, "  ({} runs skipped themselves)", skipped)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Outcome, Plan, run};
+    use crate::test_support::DEFAULT_CASE;
+    use satchel::test_harness::TestCase;
+    use std::sync::atomic::{AtomicU64, Ordering};
+
+    static STABLE: TestCase = TestCase {
+        name: "stable",
+        ..DEFAULT_CASE
+    };
+    static FLAKY: TestCase = TestCase {
+        name: "flaky",
+        ..DEFAULT_CASE
+    };
+
+    /// Fails `flaky` on every fourth of its runs.
+    fn every_fourth() -> impl Fn(&'static TestCase) -> Outcome + Sync {
+        let runs = AtomicU64::new(0);
+        move |case| {
+            if case.name == "flaky" && runs.fetch_add(1, Ordering::SeqCst) % 4 == 3 {
+                Outcome::Failed("Unexpected panic: boom".to_string())
+            } else {
+                Outcome::Passed
+            }
+        }
+    }
+
+    #[test]
+    fn reports_the_first_failure_and_the_pass_rates() {
+        let plan = Plan {
+            iterations: Some(8),
+            until_fail: false,
+            threads: 1,
+        };
+        let mut out = Vec::new();
+        let passed = run(&[&STABLE, &FLAKY], plan, every_fourth(), &mut out).unwrap();
+        assert!(!passed);
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            "repeat: running 2 tests 8 times on 1 thread\n\
+             iteration 4: mycrate::tests::flaky failed\n\
+             iteration 8: mycrate::tests::flaky failed\n\
+             \n\
+             first failure: iteration 4 of mycrate::tests::flaky\n\
+             Unexpected panic: boom\n\
+             \n\
+             pass rate:\n  \
+             mycrate::tests::flaky         6/8    75.0%  |###############     |\n  \
+             mycrate::tests::stable        8/8   100.0%  |####################|\n"
+        );
+    }
+
+    #[test]
+    fn until_fail_stops_at_the_first_failure() {
+        let plan = Plan {
+            iterations: None,
+            until_fail: true,
+            threads: 1,
+        };
+        let mut out = Vec::new();
+        assert!(!run(&[&FLAKY, &STABLE], plan, every_fourth(), &mut out).unwrap());
+        let out = String::from_utf8(out).unwrap();
+        assert!(out.contains("first failure: iteration 4 of"), "{}", out);
+        assert!(
+            out.contains("mycrate::tests::stable        3/3 "),
+            "{}",
+            out
+        );
+    }
+}
@@ -0,0 +1,161 @@
+use crate::CaseError;
+use crate::bench::BenchResult;
+use crate::capture::Output;
+use crate::json::Events;
+use libtest_mimic::Failed;
+use satchel::test_harness::TestCase;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// Final state of a case in a run.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum Status {
+    Passed,
+    Failed,
+    /// A fixture of the case failed to set up or tear down.
+    Error,
+    /// Not executed; carries the ignore reason if there is one.
+    Skipped(Option<String>),
+    /// Benchmark that produced a measurement.
+    Measured(BenchResult),
+}
+
+/// Outcome of a single case, collected for the machine-readable reports.
+#[derive(Debug, Clone)]
+pub(crate) struct CaseReport {
+    pub case: &'static TestCase,
+    pub status: Status,
+    pub duration: Duration,
+    /// Failure message, including the source location.
+    pub message: Option<String>,
+    /// Captured output of the case, if the runner captured any.
+    pub stdout: Option<String>,
+    pub stderr: Option<String>,
+    /// Failure messages of the attempts before the last one, for a case that was retried.
+    pub retried: Vec<String>,
+}
+
+impl CaseReport {
+    /// Whether the case passed after failing at first.
+    pub fn is_flaky(&self) -> bool {
+        self.status == Status::Passed && !self.retried.is_empty()
+    }
+}
+
+/// What running a case produced: the outcome and captured output of its last attempt, and the
+/// failure messages of the attempts before it.
+pub(crate) struct CaseRun<T> {
+    pub result: Result<T, CaseError>,
+    pub output: Option<Output>,
+    pub retried: Vec<String>,
+}
+
+/// Collects [`CaseReport`]s from the trials, which libtest-mimic may run on several threads.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct Recorder {
+    reports: Arc<Mutex<Vec<CaseReport>>>,
+    /// Stream that each case is written to as it starts and finishes, for `--format json`.
+    events: Option<Events>,
+}
+
+impl Recorder {
+    pub fn new(events: Option<Events>) -> Self {
+        Recorder {
+            reports: Arc::default(),
+            events,
+        }
+    }
+
+    /// Runs `f` for `case`, recording its duration, outcome and the output it captured. Returns
+    /// `Ok(Err(reason))` for a case that skipped itself while running.
+    pub fn measure<T>(
+        &self,
+        case: &'static TestCase,
+        f: impl FnOnce() -> CaseRun<T>,
+        status_of: impl FnOnce(&T) -> Status,
+    ) -> Result<Result<T, Option<String>>, Failed> {
+        if let Some(events) = &self.events {
+            events.test_started(case);
+        }
+        let start = Instant::now();
+        let CaseRun {
+            result,
+            output,
+            retried,
+        } = f();
+        let duration = start.elapsed();
+        let (status, message, result) = match result {
+            Ok(value) => (status_of(&value), None, Ok(Ok(value))),
+            Err(CaseError::Failed(failed)) => (
+                Status::Failed,
+                failed.message().map(str::to_string),
+                Err(failed),
+            ),
+            Err(CaseError::Fixture(failed)) => (
+                Status::Error,
+                failed.message().map(str::to_string),
+                Err(failed),
+            ),
+            Err(CaseError::Skipped(reason)) => {
+                (Status::Skipped(reason.clone()), None, Ok(Err(reason)))
+            }
+        };
+        self.record(CaseReport {
+            case,
+            status,
+            duration,
+            message,
+            stdout: output
+                .as_ref()
+                .map(|output| output.stdout.clone())
+                .filter(|stdout| !stdout.is_empty()),
+            stderr: output
+                .map(|output| output.stderr)
+                .filter(|stderr| !stderr.is_empty()),
+            retried,
+        });
+        result
+    }
+
+    pub fn record(&self, report: CaseReport) {
+        if let Some(events) = &self.events {
+            events.test_finished(&report);
+        }
+        self.reports.lock().unwrap().push(report);
+    }
+
+    /// Returns the recorded reports, ordered like `cases`. Cases that libtest-mimic skipped
+    /// without running them are reported as [`Status::Skipped`] with the ignore reason paired
+    /// with them, and written to the event stream now; cases filtered out of the run should not
+    /// be passed in.
+    pub fn finish(&self, cases: &[(&'static TestCase, Option<String>)]) -> Vec<CaseReport> {
+        let mut recorded = std::mem::take(&mut *self.reports.lock().unwrap());
+        cases
+            .iter()
+            .map(|(case, ignore_reason)| {
+                match recorded
+                    .iter()
+                    .position(|report| std::ptr::eq(report.case, *case))
+                {
+                    Some(index) => recorded.swap_remove(index),
+                    None => {
+                        let report = CaseReport {
+                            case,
+                            status: Status::Skipped(ignore_reason.clone()),
+                            duration: Duration::ZERO,
+                            message: None,
+                            stdout: None,
+                            stderr: None,
+                            retried: Vec::new(),
+                        };
+                        if let Some(events) = &self.events {
+                            events.test_started(case);
+                            events.test_finished(&report);
+                        }
+                        report
+                    }
+                }
+            })
+            .collect()
+    }
+}
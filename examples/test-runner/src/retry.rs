@@ -0,0 +1,113 @@
+//! Retries of failing tests, from `#[test(retries = N)]` and `--retries N`.
+//!
+//! A test that fails gets up to N more attempts, each one run like the first: with its own
+//! test-scoped fixtures, its own captured output and, if it runs in a child process, its own
+//! process. A test that passes after failing is flaky: it passes the run, but the reports list it
+//! with the failures of its earlier attempts so it can be tracked.
+
+use crate::full_name;
+use crate::report::CaseReport;
+use satchel::TestKind;
+use satchel::test_harness::TestCase;
+use std::io::{self, Write};
+
+/// Number of extra attempts of `case`: the larger of its own `retries` and `default`, the
+/// value of `--retries`. The older `retry_on_failure` marker counts as one retry. Benchmarks
+/// are not retried.
+pub(crate) fn retries(case: &TestCase, default: u32) -> u32 {
+    if case.kind == TestKind::Benchmark {
+        return 0;
+    }
+    let marker = u32::from(case.has_attr("retry_on_failure"));
+    case.retries.max(default).max(marker)
+}
+
+/// Adds the number of attempts to the message of a test that failed every one of them.
+pub(crate) fn note_attempts(message: &str, attempts: usize) -> String {
+    format!("{}\n(failed all {} attempts)", message, attempts)
+}
+
+/// Lists the flaky cases in `reports` after the summary of the run.
+pub(crate) fn write_flaky(reports: &[CaseReport], out: &mut impl Write) -> io::Result<()> {
+    let flaky: Vec<&CaseReport> = reports.iter().filter(|report| report.is_flaky()).collect();
+    if flaky.is_empty() {
+        return Ok(());
+    }
+    writeln!(out, "\nflaky tests (passed after failing):")?;
+    for report in flaky {
+        writeln!(
+            out,
+            "    {} (passed on attempt {})",
+            full_name(report.case),
+            report.retried.len() + 1
+        )?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{retries, write_flaky};
+    use crate::report::{CaseReport, Status};
+    use crate::test_support::DEFAULT_CASE;
+    use satchel::test_harness::{CaseAttribute, TestCase};
+    use satchel::{AttributeValue, TestKind};
+    use std::time::Duration;
+
+    static PLAIN: TestCase = TestCase {
+        name: "plain",
+        ..DEFAULT_CASE
+    };
+    static RETRIED: TestCase = TestCase {
+        name: "retried",
+        retries: 3,
+        ..DEFAULT_CASE
+    };
+    static MARKED: TestCase = TestCase {
+        name: "marked",
+        case_attributes: &[CaseAttribute {
+            key: "retry_on_failure",
+            value: AttributeValue::Flag,
+        }],
+        ..DEFAULT_CASE
+    };
+    static BENCH: TestCase = TestCase {
+        name: "bench",
+        kind: TestKind::Benchmark,
+        retries: 3,
+        ..DEFAULT_CASE
+    };
+
+    #[test]
+    fn the_larger_retry_count_applies() {
+        assert_eq!(retries(&PLAIN, 0), 0);
+        assert_eq!(retries(&PLAIN, 2), 2);
+        assert_eq!(retries(&RETRIED, 2), 3);
+        assert_eq!(retries(&MARKED, 0), 1);
+        assert_eq!(retries(&BENCH, 2), 0);
+    }
+
+    #[test]
+    fn flaky_cases_are_listed_with_their_attempt() {
+        let report = |case, status, retried: &[&str]| CaseReport {
+            case,
+            status,
+            duration: Duration::ZERO,
+            message: None,
+            stdout: None,
+            stderr: None,
+            retried: retried.iter().map(|message| message.to_string()).collect(),
+        };
+        let reports = [
+            report(&PLAIN, Status::Passed, &[]),
+            report(&RETRIED, Status::Passed, &["boom", "boom"]),
+            report(&MARKED, Status::Failed, &["boom"]),
+        ];
+        let mut out = Vec::new();
+        write_flaky(&reports, &mut out).unwrap();
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            "\nflaky tests (passed after failing):\n    mycrate::tests::retried (passed on attempt 3)\n"
+        );
+    }
+}
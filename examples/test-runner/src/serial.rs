@@ -0,0 +1,87 @@
+//! Serial groups from `#[test(serial)]` and `#[test(serial_group = "...")]`.
+//!
+//! Every group has a lock that its cases hold while they run, so no two cases of a group run at
+//! the same time while the other cases still run in parallel. A case waiting for its group
+//! keeps a test thread busy, like a case waiting for any other lock.
+
+use satchel::test_harness::TestCase;
+use std::collections::HashMap;
+use std::sync::{Mutex, MutexGuard};
+
+/// The locks of the serial groups of a run.
+#[derive(Debug, Default)]
+pub(crate) struct SerialGroups {
+    locks: HashMap<&'static str, Mutex<()>>,
+}
+
+impl SerialGroups {
+    pub fn new(cases: &[&'static TestCase]) -> Self {
+        SerialGroups {
+            locks: cases
+                .iter()
+                .filter_map(|case| case.serial_group)
+                .map(|group| (group, Mutex::new(())))
+                .collect(),
+        }
+    }
+
+    /// Waits until no other case of the group of `case` runs, and keeps them from starting
+    /// until the guard is dropped. Cases outside any group need no guard.
+    pub fn lock(&self, case: &TestCase) -> Option<MutexGuard<'_, ()>> {
+        let lock = self.locks.get(case.serial_group?)?;
+        // Cases run under `catch_unwind`, but a poisoned lock must not stop the others anyway.
+        Some(lock.lock().unwrap_or_else(|poisoned| poisoned.into_inner()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::SerialGroups;
+    use crate::test_support::DEFAULT_CASE;
+    use satchel::test_harness::TestCase;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::time::Duration;
+
+    static DB_FIRST: TestCase = TestCase {
+        name: "db_first",
+        serial_group: Some("db"),
+        ..DEFAULT_CASE
+    };
+    static DB_SECOND: TestCase = TestCase {
+        name: "db_second",
+        serial_group: Some("db"),
+        ..DEFAULT_CASE
+    };
+    static CACHE: TestCase = TestCase {
+        name: "cache",
+        serial_group: Some("cache"),
+        ..DEFAULT_CASE
+    };
+    static FREE: TestCase = TestCase {
+        name: "free",
+        ..DEFAULT_CASE
+    };
+
+    #[test]
+    fn cases_of_a_group_never_overlap() {
+        let groups = SerialGroups::new(&[&DB_FIRST, &DB_SECOND, &CACHE, &FREE]);
+        assert!(groups.lock(&FREE).is_none());
+        let _cache = groups.lock(&CACHE).unwrap();
+
+        let running = AtomicUsize::new(0);
+        let most = AtomicUsize::new(0);
+        std::thread::scope(|scope| {
+            for case in [&DB_FIRST, &DB_SECOND].repeat(4) {
+                let (groups, running, most) = (&groups, &running, &most);
+                scope.spawn(move || {
+                    let _guard = groups.lock(case);
+                    let now = running.fetch_add(1, Ordering::SeqCst) + 1;
+                    most.fetch_max(now, Ordering::SeqCst);
+                    std::thread::sleep(Duration::from_millis(5));
+                    running.fetch_sub(1, Ordering::SeqCst);
+                });
+            }
+        });
+        assert_eq!(most.load(Ordering::SeqCst), 1);
+    }
+}
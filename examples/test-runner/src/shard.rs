@@ -0,0 +1,317 @@
+//! Sharding for `--shard-index I --shard-count N`, which splits the cases of a run across N
+//! machines.
+//!
+//! Every shard computes the same partition on its own, so the cases must be the same on every
+//! machine. By default a case goes to the shard given by a stable hash of its
+//! `module_path::name`, which keeps cases on their shard as others are added or removed. With a
+//! timing file from `--save-timings`, the cases are instead dealt out longest first, each to the
+//! shard with the least total time so far, so the shards take about equally long:
+//!
+//! ```json
+//! { "somelib::tests::it_works": 0.012, "somelib::tests::loads_fixtures": 4.2 }
+//! ```
+//!
+//! Cases missing from the timing file are assumed to take the median time of the others. Cases
+//! connected by dependencies go to the same shard together, so a case never runs without the
+//! cases it depends on: the group is hashed by its smallest name, or dealt out by its total time.
+
+use crate::report::{CaseReport, Status};
+use crate::{depends, full_name};
+use satchel::test_harness::TestCase;
+use serde_json::{Map, Value};
+use std::io;
+use std::path::Path;
+
+/// Time assumed for every case when the timing file knows none of them.
+const DEFAULT_SECONDS: f64 = 1.0;
+
+/// The part of the run this process executes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct Shard {
+    pub index: usize,
+    pub count: usize,
+}
+
+impl Shard {
+    pub fn new(index: usize, count: usize) -> Result<Self, String> {
+        if count == 0 {
+            return Err("--shard-count must be at least 1".to_string());
+        }
+        if index >= count {
+            return Err(format!(
+                "--shard-index {} is out of range for --shard-count {}; shards are numbered from 0",
+                index, count
+            ));
+        }
+        Ok(Shard { index, count })
+    }
+}
+
+/// 64-bit FNV-1a, which unlike the hasher of `std` is the same in every build and release.
+fn stable_hash(text: &str) -> u64 {
+    text.bytes().fold(0xcbf2_9ce4_8422_2325, |hash, byte| {
+        (hash ^ u64::from(byte)).wrapping_mul(0x0000_0100_0000_01b3)
+    })
+}
+
+/// Keeps the cases of `shard`, chosen by hash or, with `timings`, balanced by their durations
+/// in seconds.
+pub(crate) fn select(
+    cases: &mut Vec<&'static TestCase>,
+    shard: Shard,
+    timings: Option<&Map<String, Value>>,
+) {
+    let names: Vec<String> = cases.iter().map(|case| full_name(case)).collect();
+    let groups = groups(cases, &names);
+    let assigned: Vec<usize> = match timings {
+        None => groups
+            .iter()
+            .map(|&root| (stable_hash(&names[root]) % shard.count as u64) as usize)
+            .collect(),
+        Some(timings) => balance(&names, &groups, timings, shard.count),
+    };
+    let mut assigned = assigned.into_iter();
+    cases.retain(|_| assigned.next() == Some(shard.index));
+}
+
+/// For every case of `cases`, named `names`, the position of the case with the smallest name
+/// among the cases connected to it by dependencies.
+fn groups(cases: &[&'static TestCase], names: &[String]) -> Vec<usize> {
+    fn root(parents: &mut [usize], mut position: usize) -> usize {
+        while parents[position] != position {
+            parents[position] = parents[parents[position]];
+            position = parents[position];
+        }
+        position
+    }
+
+    let mut parents: Vec<usize> = (0..cases.len()).collect();
+    for (position, dependencies) in depends::edges(cases).into_iter().enumerate() {
+        for dependency in dependencies {
+            let (a, b) = (root(&mut parents, position), root(&mut parents, dependency));
+            if names[a] < names[b] {
+                parents[b] = a;
+            } else {
+                parents[a] = b;
+            }
+        }
+    }
+    (0..cases.len())
+        .map(|position| root(&mut parents, position))
+        .collect()
+}
+
+/// Deals the groups of cases out by their total time, longest first, each to the shard with the
+/// least time so far; ties go to the lower shard and the name breaks ties between groups, so
+/// every shard agrees.
+fn balance(
+    names: &[String],
+    groups: &[usize],
+    timings: &Map<String, Value>,
+    count: usize,
+) -> Vec<usize> {
+    let known: Vec<Option<f64>> = names
+        .iter()
+        .map(|name| timings.get(name).and_then(Value::as_f64))
+        .collect();
+    let mut sorted: Vec<f64> = known.iter().flatten().copied().collect();
+    sorted.sort_by(f64::total_cmp);
+    let fallback = match sorted.len() {
+        0 => DEFAULT_SECONDS,
+        n => sorted[n / 2],
+    };
+    let mut seconds = vec![0.0f64; names.len()];
+    for (case, &root) in groups.iter().enumerate() {
+        seconds[root] += known[case].unwrap_or(fallback);
+    }
+
+    let mut order: Vec<usize> = (0..names.len())
+        .filter(|&position| groups[position] == position)
+        .collect();
+    order.sort_by(|&a, &b| {
+        seconds[b]
+            .total_cmp(&seconds[a])
+            .then_with(|| names[a].cmp(&names[b]))
+    });
+    let mut loads = vec![0.0f64; count];
+    let mut assigned = vec![0; names.len()];
+    for root in order {
+        let (shard, _) = loads
+            .iter()
+            .enumerate()
+            .min_by(|(_, a), (_, b)| a.total_cmp(b))
+            .unwrap();
+        loads[shard] += seconds[root];
+        assigned[root] = shard;
+    }
+    groups.iter().map(|&root| assigned[root]).collect()
+}
+
+/// Reads a timing file written by [`save_timings`].
+pub(crate) fn load_timings(path: &Path) -> io::Result<Map<String, Value>> {
+    let text = std::fs::read_to_string(path)?;
+    match serde_json::from_str(&text)? {
+        Value::Object(entries) => Ok(entries),
+        _ => Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            "expected a JSON object",
+        )),
+    }
+}
+
+/// Merges the durations of the cases that ran into the timing file at `path`, so the shards of
+/// a run, or several libraries, can add to the same file.
+pub(crate) fn save_timings(path: &Path, reports: &[CaseReport]) -> io::Result<()> {
+    let mut entries = match load_timings(path) {
+        Ok(entries) => entries,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => Map::new(),
+        Err(e) => return Err(e),
+    };
+    for report in reports {
+        if !matches!(report.status, Status::Skipped(_)) {
+            entries.insert(
+                full_name(report.case),
+                Value::from(report.duration.as_secs_f64()),
+            );
+        }
+    }
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir)?;
+    }
+    let mut text = serde_json::to_string_pretty(&Value::Object(entries))?;
+    text.push('\n');
+    std::fs::write(path, text)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Shard, balance, select, stable_hash};
+    use crate::test_support::DEFAULT_CASE;
+    use satchel::test_harness::TestCase;
+    use serde_json::{Map, Value, json};
+
+    static CASES: [TestCase; 6] = [
+        TestCase {
+            name: "a",
+            ..DEFAULT_CASE
+        },
+        TestCase {
+            name: "b",
+            ..DEFAULT_CASE
+        },
+        TestCase {
+            name: "c",
+            ..DEFAULT_CASE
+        },
+        TestCase {
+            name: "d",
+            ..DEFAULT_CASE
+        },
+        TestCase {
+            name: "e",
+            ..DEFAULT_CASE
+        },
+        TestCase {
+            name: "f",
+            ..DEFAULT_CASE
+        },
+    ];
+
+    fn shards(count: usize, timings: Option<&Map<String, Value>>) -> Vec<Vec<&'static str>> {
+        (0..count)
+            .map(|index| {
+                let mut cases: Vec<&'static TestCase> = CASES.iter().collect();
+                select(&mut cases, Shard::new(index, count).unwrap(), timings);
+                cases.iter().map(|case| case.name).collect()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn hashed_shards_partition_the_cases_stably() {
+        // The partition must not change between builds, or shards would skip or repeat cases.
+        assert_eq!(stable_hash(""), 0xcbf2_9ce4_8422_2325);
+        assert_eq!(stable_hash("a"), 0xaf63_dc4c_8601_ec8c);
+
+        let shards = shards(3, None);
+        let mut all: Vec<&str> = shards.concat();
+        all.sort_unstable();
+        assert_eq!(all, ["a", "b", "c", "d", "e", "f"]);
+        assert_eq!(shards, self::shards(3, None));
+    }
+
+    #[test]
+    fn timings_balance_the_shards() {
+        let timings = json!({
+            "mycrate::tests::a": 8.0,
+            "mycrate::tests::b": 5.0,
+            "mycrate::tests::c": 4.0,
+            "mycrate::tests::d": 3.0,
+            "mycrate::tests::e": 1.0,
+        });
+        let timings = timings.as_object().unwrap();
+        // `f` is unknown and assumed to take the median of 4 seconds.
+        assert_eq!(
+            shards(2, Some(timings)),
+            [vec!["a", "e", "f"], vec!["b", "c", "d"]]
+        );
+
+        let names: Vec<String> = ["x", "y"].iter().map(|name| name.to_string()).collect();
+        assert_eq!(balance(&names, &[0, 1], &Map::new(), 2), [0, 1]);
+    }
+
+    #[test]
+    fn cases_connected_by_dependencies_share_a_shard() {
+        static CHAIN: [TestCase; 3] = [
+            TestCase {
+                name: "connect",
+                ..DEFAULT_CASE
+            },
+            TestCase {
+                name: "migrate",
+                depends_on: &["connect"],
+                ..DEFAULT_CASE
+            },
+            TestCase {
+                name: "query",
+                depends_on: &["migrate"],
+                ..DEFAULT_CASE
+            },
+        ];
+        // On their own, the cases of the chain would be split up.
+        let hashed: Vec<u64> = ["connect", "migrate", "query"]
+            .iter()
+            .map(|name| stable_hash(&format!("mycrate::tests::{}", name)) % 2)
+            .collect();
+        assert!(hashed.iter().any(|&shard| shard != hashed[0]));
+
+        let timings = json!({ "mycrate::tests::connect": 9.0, "mycrate::tests::a": 9.0 });
+        for timings in [None, timings.as_object()] {
+            for count in 2..=4 {
+                let shards: Vec<Vec<&str>> = (0..count)
+                    .map(|index| {
+                        let mut cases: Vec<&'static TestCase> =
+                            CASES.iter().chain(&CHAIN).collect();
+                        select(&mut cases, Shard::new(index, count).unwrap(), timings);
+                        cases.iter().map(|case| case.name).collect()
+                    })
+                    .collect();
+                assert_eq!(shards.concat().len(), CASES.len() + CHAIN.len());
+                assert!(
+                    shards
+                        .iter()
+                        .any(|shard| shard.ends_with(&["connect", "migrate", "query"])),
+                    "{:?}",
+                    shards
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn shard_index_must_be_below_the_count() {
+        assert!(Shard::new(0, 1).is_ok());
+        assert!(Shard::new(2, 2).is_err());
+        assert!(Shard::new(0, 0).is_err());
+    }
+}
@@ -0,0 +1,101 @@
+//! Random test order for `--shuffle` and `--shuffle-seed N`.
+//!
+//! The cases are sorted by `module_path::name` and then shuffled with a generator seeded by N,
+//! so a seed gives the same order in every build of the same tests, whatever their link order.
+
+use crate::full_name;
+use satchel::test_harness::TestCase;
+use std::hash::{BuildHasher, RandomState};
+
+/// SplitMix64, a small generator whose sequence is fixed by its seed.
+struct SplitMix64(u64);
+
+impl SplitMix64 {
+    fn next(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+
+    /// A number below `bound`, without the bias of a plain remainder.
+    fn below(&mut self, bound: u64) -> u64 {
+        let zone = u64::MAX - u64::MAX % bound;
+        loop {
+            let value = self.next();
+            if value < zone {
+                return value % bound;
+            }
+        }
+    }
+}
+
+/// A seed for `--shuffle` without `--shuffle-seed`.
+pub(crate) fn random_seed() -> u64 {
+    RandomState::new().hash_one(std::time::SystemTime::now())
+}
+
+/// Puts `cases` in the order given by `seed`.
+pub(crate) fn shuffle(cases: &mut [&'static TestCase], seed: u64) {
+    cases.sort_by_cached_key(|case| full_name(case));
+    let mut rng = SplitMix64(seed);
+    for i in (1..cases.len()).rev() {
+        let j = rng.below(i as u64 + 1) as usize;
+        cases.swap(i, j);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{SplitMix64, shuffle};
+    use crate::test_support::DEFAULT_CASE;
+    use satchel::test_harness::TestCase;
+
+    static CASES: [TestCase; 5] = [
+        TestCase {
+            name: "e",
+            ..DEFAULT_CASE
+        },
+        TestCase {
+            name: "a",
+            ..DEFAULT_CASE
+        },
+        TestCase {
+            name: "d",
+            ..DEFAULT_CASE
+        },
+        TestCase {
+            name: "b",
+            ..DEFAULT_CASE
+        },
+        TestCase {
+            name: "c",
+            ..DEFAULT_CASE
+        },
+    ];
+
+    fn order(cases: &[&'static TestCase], seed: u64) -> Vec<&'static str> {
+        let mut cases = cases.to_vec();
+        shuffle(&mut cases, seed);
+        cases.iter().map(|case| case.name).collect()
+    }
+
+    #[test]
+    fn a_seed_gives_the_same_order_whatever_the_link_order() {
+        // The first output of SplitMix64 seeded with 0, as published with the algorithm.
+        assert_eq!(SplitMix64(0).next(), 0xe220_a839_7b1d_cdaf);
+
+        let linked: Vec<&'static TestCase> = CASES.iter().collect();
+        let reversed: Vec<&'static TestCase> = CASES.iter().rev().collect();
+        assert_eq!(order(&linked, 42), order(&reversed, 42));
+
+        let mut sorted = order(&linked, 42);
+        sorted.sort_unstable();
+        assert_eq!(sorted, ["a", "b", "c", "d", "e"]);
+        assert!(
+            (0..8).any(|seed| order(&linked, seed) != order(&linked, 42)),
+            "other seeds give other orders"
+        );
+    }
+}
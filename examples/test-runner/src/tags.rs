@@ -0,0 +1,177 @@
+//! Tag expressions for `--tags`, such as `smoke & !slow` or `(gpu | nightly) & !flaky`.
+//!
+//! `!` binds tighter than `&`, which binds tighter than `|`; parentheses group. Tags are made
+//! of letters, digits, `_`, `-`, `.` and `:`, like the tags accepted by `#[tag(...)]`.
+
+use std::fmt;
+use std::str::FromStr;
+
+/// A parsed `--tags` expression, matched against [`TestCase::tags`](satchel::TestCase::tags).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TagExpr {
+    Tag(String),
+    Not(Box<TagExpr>),
+    And(Box<TagExpr>, Box<TagExpr>),
+    Or(Box<TagExpr>, Box<TagExpr>),
+}
+
+impl TagExpr {
+    /// Whether a case tagged with `tags` is selected by the expression.
+    pub fn matches(&self, tags: &[&str]) -> bool {
+        match self {
+            TagExpr::Tag(tag) => tags.contains(&tag.as_str()),
+            TagExpr::Not(expr) => !expr.matches(tags),
+            TagExpr::And(left, right) => left.matches(tags) && right.matches(tags),
+            TagExpr::Or(left, right) => left.matches(tags) || right.matches(tags),
+        }
+    }
+}
+
+impl FromStr for TagExpr {
+    type Err = String;
+
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        let mut parser = Parser { text, pos: 0 };
+        let expr = parser.or()?;
+        parser.skip_whitespace();
+        match parser.peek() {
+            None => Ok(expr),
+            Some(c) => Err(parser.error(&format!("unexpected `{}`", c))),
+        }
+    }
+}
+
+/// Renders the expression fully parenthesized, e.g. `(smoke & (!slow))`.
+impl fmt::Display for TagExpr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TagExpr::Tag(tag) => f.write_str(tag),
+            TagExpr::Not(expr) => write!(f, "(!{})", expr),
+            TagExpr::And(left, right) => write!(f, "({} & {})", left, right),
+            TagExpr::Or(left, right) => write!(f, "({} | {})", left, right),
+        }
+    }
+}
+
+fn is_tag_char(c: char) -> bool {
+    c.is_ascii_alphanumeric() || matches!(c, '_' | '-' | '.' | ':')
+}
+
+// Recursive descent over `or := and ('|' and)*`, `and := unary ('&' unary)*`,
+// `unary := '!' unary | '(' or ')' | tag`.
+struct Parser<'a> {
+    text: &'a str,
+    pos: usize,
+}
+
+impl Parser<'_> {
+    fn peek(&self) -> Option<char> {
+        self.text[self.pos..].chars().next()
+    }
+
+    fn skip_whitespace(&mut self) {
+        while let Some(c) = self.peek().filter(|c| c.is_whitespace()) {
+            self.pos += c.len_utf8();
+        }
+    }
+
+    fn eat(&mut self, expected: char) -> bool {
+        self.skip_whitespace();
+        if self.peek() == Some(expected) {
+            self.pos += expected.len_utf8();
+            true
+        } else {
+            false
+        }
+    }
+
+    fn error(&self, message: &str) -> String {
+        format!("{} at position {} of `{}`", message, self.pos, self.text)
+    }
+
+    fn or(&mut self) -> Result<TagExpr, String> {
+        let mut expr = self.and()?;
+        while self.eat('|') {
+            expr = TagExpr::Or(Box::new(expr), Box::new(self.and()?));
+        }
+        Ok(expr)
+    }
+
+    fn and(&mut self) -> Result<TagExpr, String> {
+        let mut expr = self.unary()?;
+        while self.eat('&') {
+            expr = TagExpr::And(Box::new(expr), Box::new(self.unary()?));
+        }
+        Ok(expr)
+    }
+
+    fn unary(&mut self) -> Result<TagExpr, String> {
+        if self.eat('!') {
+            return Ok(TagExpr::Not(Box::new(self.unary()?)));
+        }
+        if self.eat('(') {
+            let expr = self.or()?;
+            if !self.eat(')') {
+                return Err(self.error("expected `)`"));
+            }
+            return Ok(expr);
+        }
+        self.skip_whitespace();
+        let start = self.pos;
+        while let Some(c) = self.peek().filter(|c| is_tag_char(*c)) {
+            self.pos += c.len_utf8();
+        }
+        if start == self.pos {
+            return Err(self.error("expected a tag"));
+        }
+        Ok(TagExpr::Tag(self.text[start..self.pos].to_string()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::TagExpr;
+
+    fn parse(text: &str) -> TagExpr {
+        text.parse().unwrap()
+    }
+
+    #[test]
+    fn precedence_and_grouping() {
+        assert_eq!(parse("smoke & !slow").to_string(), "(smoke & (!slow))");
+        assert_eq!(parse("a | b & c").to_string(), "(a | (b & c))");
+        assert_eq!(parse("(a | b) & c").to_string(), "((a | b) & c)");
+        assert_eq!(
+            parse("!!needs-gpu-stub").to_string(),
+            "(!(!needs-gpu-stub))"
+        );
+        assert_eq!(parse(" a&b|c ").to_string(), "((a & b) | c)");
+    }
+
+    #[test]
+    fn expressions_select_by_tags() {
+        let expr = parse("smoke & !slow");
+        assert!(expr.matches(&["smoke"]));
+        assert!(!expr.matches(&["smoke", "slow"]));
+        assert!(!expr.matches(&[]));
+        assert!(parse("!nightly").matches(&[]));
+        assert!(parse("gpu | nightly").matches(&["nightly"]));
+    }
+
+    #[test]
+    fn malformed_expressions_are_rejected() {
+        assert_eq!(
+            "smoke &".parse::<TagExpr>(),
+            Err("expected a tag at position 7 of `smoke &`".to_string())
+        );
+        assert_eq!(
+            "(smoke".parse::<TagExpr>(),
+            Err("expected `)` at position 6 of `(smoke`".to_string())
+        );
+        assert_eq!(
+            "smoke slow".parse::<TagExpr>(),
+            Err("unexpected `s` at position 6 of `smoke slow`".to_string())
+        );
+        assert!("".parse::<TagExpr>().is_err());
+    }
+}
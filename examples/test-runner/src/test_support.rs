@@ -0,0 +1,28 @@
+//! Shared pieces of the unit tests of the runner.
+
+use satchel::test_harness::{SourceLocation, TestCase};
+use satchel::{EntryPoint, TestKind};
+
+/// A passing unit test `mycrate::tests::it_works` without any attributes. Tests describe their
+/// cases as changes to it, e.g. `TestCase { name: "slow", ..DEFAULT_CASE }`.
+pub(crate) const DEFAULT_CASE: TestCase = TestCase {
+    name: "it_works",
+    module_path: "mycrate::tests",
+    location: SourceLocation {
+        file: "src/lib.rs",
+        line: 1,
+        column: 1,
+    },
+    kind: TestKind::Unit,
+    test_fn: EntryPoint::Plain(|| {}),
+    should_panic: None,
+    ignore: None,
+    case_attributes: &[],
+    tags: &[],
+    executor: None,
+    timeout: None,
+    retries: 0,
+    serial_group: None,
+    depends_on: &[],
+    fixtures: &[],
+};
@@ -0,0 +1,45 @@
+//! Bisects a case that fails only after another case changed global state, in the order given by
+//! a shuffle seed, and checks that the culprit is found.
+
+use satchel::test;
+use std::sync::atomic::{AtomicBool, Ordering};
+use test_runner::RunnerArgs;
+
+static POLLUTED: AtomicBool = AtomicBool::new(false);
+
+#[test]
+fn formats_dates() {}
+
+#[test]
+fn leaves_the_locale_changed() {
+    POLLUTED.store(true, Ordering::SeqCst);
+}
+
+#[test]
+fn parses_numbers() {}
+
+#[test]
+fn reads_config() {}
+
+#[test]
+fn sorts_names() {}
+
+#[test]
+fn expects_the_default_locale() {
+    assert!(!POLLUTED.load(Ordering::SeqCst), "the locale was changed");
+}
+
+fn bisect(target: &str) -> bool {
+    // Seed 0 runs `leaves_the_locale_changed` and three other cases before the target.
+    let args = RunnerArgs::parse_from(["bisect", "--shuffle-seed", "0", "--bisect", target]);
+    // Children run a sequence of cases and exit inside `run_tests`.
+    test_runner::run_tests(satchel::get_tests!(), args)
+}
+
+fn main() {
+    assert!(bisect("bisect::expects_the_default_locale"));
+    assert!(
+        !bisect("bisect::no_such_test"),
+        "an unknown test has no culprit"
+    );
+}
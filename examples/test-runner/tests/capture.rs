@@ -0,0 +1,72 @@
+//! Runs cases that write to stdout and stderr, from Rust and through the file descriptors as C
+//! code does, and checks that the output is captured per case in the JUnit report, on the default
+//! number of threads, on one and on several.
+
+use satchel::test;
+use std::ffi::OsStr;
+use test_runner::RunnerArgs;
+
+fn write_to_fd(fd: i32, text: &str) {
+    #[cfg(unix)]
+    unsafe {
+        libc::write(fd, text.as_ptr().cast(), text.len());
+    }
+    #[cfg(not(unix))]
+    let _ = (fd, text);
+}
+
+#[test]
+fn prints_and_passes() {
+    println!("printed by the passing case");
+    write_to_fd(2, "written to fd 2 by the passing case\n");
+}
+
+#[test]
+fn prints_and_fails() {
+    println!("printed by the failing case");
+    write_to_fd(1, "written to fd 1 by the failing case\n");
+    panic!("boom");
+}
+
+/// Runs the cases with `threads` and checks the report; several threads capture through the
+/// pipes of child processes, a single one by redirecting this process.
+fn run_with(threads: &[&str]) {
+    let report = std::env::temp_dir().join(format!("satchel-capture-{}.xml", std::process::id()));
+    let mut args = vec![OsStr::new("capture")];
+    args.extend(threads.iter().map(OsStr::new));
+    args.extend(["--junit-xml".as_ref(), report.as_os_str()]);
+    let args = RunnerArgs::parse_from(args);
+
+    let passed = test_runner::run_tests(satchel::get_tests!(), args);
+    let xml = std::fs::read_to_string(&report).unwrap();
+    let _ = std::fs::remove_file(&report);
+
+    assert!(!passed, "the panicking case must fail");
+    assert!(
+        xml.contains("<system-out>printed by the passing case\n</system-out>"),
+        "{}",
+        xml
+    );
+    #[cfg(unix)]
+    {
+        assert!(
+            xml.contains("<system-err>written to fd 2 by the passing case\n</system-err>"),
+            "{}",
+            xml
+        );
+        assert!(
+            xml.contains(
+                "---- stdout ----\nprinted by the failing case\nwritten to fd 1 by the failing case"
+            ),
+            "{}",
+            xml
+        );
+    }
+}
+
+fn main() {
+    run_with(&[]);
+    run_with(&["--test-threads", "1"]);
+    run_with(&["--test-threads", "4"]);
+    println!("capture: the failures above are expected");
+}
@@ -0,0 +1,66 @@
+//! Runs a chain of dependent tests, declared in reverse, on several threads and checks that each
+//! runs after the one it depends on and that the tests after a failure are skipped with a reason.
+
+use satchel::test;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Duration;
+use test_runner::RunnerArgs;
+
+static CONNECTED: AtomicBool = AtomicBool::new(false);
+static QUERIED: AtomicBool = AtomicBool::new(false);
+static REPORTED: AtomicBool = AtomicBool::new(false);
+
+#[test(depends_on = [query_users])]
+fn report() {
+    REPORTED.store(true, Ordering::SeqCst);
+}
+
+#[test(depends_on = [migrate_schema])]
+fn query_users() {
+    QUERIED.store(true, Ordering::SeqCst);
+}
+
+#[test(depends_on = [connect])]
+fn migrate_schema() {
+    assert!(CONNECTED.load(Ordering::SeqCst), "ran before `connect`");
+    panic!("migration failed");
+}
+
+#[test]
+fn connect() {
+    std::thread::sleep(Duration::from_millis(20));
+    CONNECTED.store(true, Ordering::SeqCst);
+}
+
+fn main() {
+    let report = std::env::temp_dir().join(format!("satchel-depends-{}.xml", std::process::id()));
+    // The tests check each other's statics, so they must share this process instead of being
+    // captured in processes of their own.
+    let args = RunnerArgs::parse_from([
+        "depends".as_ref(),
+        "--test-threads".as_ref(),
+        "4".as_ref(),
+        "--nocapture".as_ref(),
+        "--junit-xml".as_ref(),
+        report.as_os_str(),
+    ]);
+
+    let passed = test_runner::run_tests(satchel::get_tests!(), args);
+    let xml = std::fs::read_to_string(&report).unwrap();
+    let _ = std::fs::remove_file(&report);
+
+    assert!(!passed, "the failing migration must fail the run");
+    assert!(xml.contains("migration failed"), "{}", xml);
+    assert!(!QUERIED.load(Ordering::SeqCst) && !REPORTED.load(Ordering::SeqCst));
+    assert!(
+        xml.contains("<skipped message=\"dependency `depends::migrate_schema` failed\"/>"),
+        "{}",
+        xml
+    );
+    assert!(
+        xml.contains("<skipped message=\"dependency `depends::query_users` was skipped\"/>"),
+        "{}",
+        xml
+    );
+    println!("depends: the failure above is expected");
+}
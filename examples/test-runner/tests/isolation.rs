@@ -0,0 +1,64 @@
+//! Runs cases that abort or exit the process with `--isolate` and checks that each one is
+//! reported as a failure of its own in the JUnit report.
+
+use satchel::test;
+use test_runner::RunnerArgs;
+
+#[test]
+fn prints_and_passes() {
+    println!("hello from the child");
+}
+
+#[test]
+fn aborts() {
+    std::process::abort();
+}
+
+#[test]
+fn exits() {
+    std::process::exit(3);
+}
+
+#[test]
+fn panics() {
+    panic!("boom");
+}
+
+fn main() {
+    let report = std::env::temp_dir().join(format!("satchel-isolation-{}.xml", std::process::id()));
+    let args = RunnerArgs::parse_from([
+        "isolation".as_ref(),
+        "--isolate".as_ref(),
+        "--junit-xml".as_ref(),
+        report.as_os_str(),
+    ]);
+
+    // Children run a single case and exit inside `run_tests`.
+    let passed = test_runner::run_tests(satchel::get_tests!(), args);
+    let xml = std::fs::read_to_string(&report).unwrap();
+    let _ = std::fs::remove_file(&report);
+
+    assert!(
+        !passed,
+        "the aborting, exiting and panicking cases must fail"
+    );
+    assert!(xml.contains("tests=\"4\" failures=\"3\""), "{}", xml);
+    assert!(
+        xml.contains("<system-out>hello from the child\n</system-out>"),
+        "{}",
+        xml
+    );
+    #[cfg(unix)]
+    assert!(
+        xml.contains("Test process was killed by signal 6 (SIGABRT)"),
+        "{}",
+        xml
+    );
+    assert!(
+        xml.contains("Test process exited with status 3 before reporting a result"),
+        "{}",
+        xml
+    );
+    assert!(xml.contains("Unexpected panic: boom"), "{}", xml);
+    println!("isolation: the three failures above are expected");
+}
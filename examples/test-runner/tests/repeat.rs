@@ -0,0 +1,49 @@
+//! Repeats two tests on the default number of threads and checks that the runs are shared out among as
+//! many threads as `--test-threads` defaults to.
+
+use satchel::test;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::{Duration, Instant};
+use test_runner::RunnerArgs;
+
+static RUNNING: AtomicUsize = AtomicUsize::new(0);
+static PEAK: AtomicUsize = AtomicUsize::new(0);
+
+/// The number of runs expected to overlap: both tests, unless there is a single thread.
+fn expected() -> usize {
+    std::thread::available_parallelism().map_or(1, |n| n.get().min(2))
+}
+
+/// Stays running until the expected number of runs were running at the same time, or for a
+/// while if they never are.
+fn overlap() {
+    let running = RUNNING.fetch_add(1, Ordering::SeqCst) + 1;
+    PEAK.fetch_max(running, Ordering::SeqCst);
+    let start = Instant::now();
+    while PEAK.load(Ordering::SeqCst) < expected() && start.elapsed() < Duration::from_secs(10) {
+        std::thread::sleep(Duration::from_millis(1));
+    }
+    RUNNING.fetch_sub(1, Ordering::SeqCst);
+}
+
+#[test]
+fn first() {
+    overlap();
+}
+
+#[test]
+fn second() {
+    overlap();
+}
+
+fn main() {
+    // The runs meet through statics, so they must share this process instead of being captured
+    // in processes of their own.
+    let args = RunnerArgs::parse_from(["repeat", "--repeat", "3", "--nocapture"]);
+    assert!(test_runner::run_tests(satchel::get_tests!(), args));
+    assert_eq!(
+        PEAK.load(Ordering::SeqCst),
+        expected(),
+        "the repeated runs did not use the default number of threads"
+    );
+}
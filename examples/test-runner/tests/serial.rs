@@ -0,0 +1,73 @@
+//! Runs tests of two serial groups next to free tests on several threads and checks that tests
+//! of a group never overlap while the free tests still run in parallel.
+
+use satchel::test;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::{Duration, Instant};
+use test_runner::RunnerArgs;
+
+static IN_DB: AtomicUsize = AtomicUsize::new(0);
+static IN_DEFAULT: AtomicUsize = AtomicUsize::new(0);
+static ARRIVED: AtomicUsize = AtomicUsize::new(0);
+
+/// Holds the group `running` counts for a while and fails if another test of it is inside.
+fn exclusive(running: &AtomicUsize) {
+    assert_eq!(running.fetch_add(1, Ordering::SeqCst), 0, "overlapping");
+    std::thread::sleep(Duration::from_millis(20));
+    running.fetch_sub(1, Ordering::SeqCst);
+}
+
+/// Waits until both free tests are running at the same time.
+fn meet() {
+    ARRIVED.fetch_add(1, Ordering::SeqCst);
+    let start = Instant::now();
+    while ARRIVED.load(Ordering::SeqCst) < 2 {
+        assert!(
+            start.elapsed() < Duration::from_secs(10),
+            "the free tests did not run in parallel"
+        );
+        std::thread::sleep(Duration::from_millis(1));
+    }
+}
+
+#[test(serial_group = "db")]
+fn db_one() {
+    exclusive(&IN_DB);
+}
+
+#[test(serial_group = "db")]
+fn db_two() {
+    exclusive(&IN_DB);
+}
+
+#[test(serial_group = "db")]
+fn db_three() {
+    exclusive(&IN_DB);
+}
+
+#[test(serial)]
+fn default_one() {
+    exclusive(&IN_DEFAULT);
+}
+
+#[test(serial)]
+fn default_two() {
+    exclusive(&IN_DEFAULT);
+}
+
+#[test]
+fn free_one() {
+    meet();
+}
+
+#[test]
+fn free_two() {
+    meet();
+}
+
+fn main() {
+    // The tests meet through statics, so they must share this process instead of being captured
+    // in processes of their own.
+    let args = RunnerArgs::parse_from(["serial", "--test-threads", "4", "--nocapture"]);
+    assert!(test_runner::run_tests(satchel::get_tests!(), args));
+}
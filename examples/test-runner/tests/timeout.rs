@@ -0,0 +1,68 @@
+//! Runs a test that hangs past its timeout next to a test that runs after it and checks that the
+//! hung test fails without running on: its output stays out of the next test's capture. A test
+//! whose fixture hangs in its setup fails with its timeout too.
+
+use satchel::{fixture, test};
+use std::time::Duration;
+use test_runner::RunnerArgs;
+
+#[fixture(scope = "module")]
+fn connection() -> Vec<u8> {
+    vec![1, 2, 3]
+}
+
+#[test(timeout = "100ms", serial)]
+fn hangs(connection: &Vec<u8>) {
+    loop {
+        println!("still using {:?}", connection);
+        std::thread::sleep(Duration::from_millis(10));
+    }
+}
+
+#[fixture]
+fn stuck() -> Vec<u8> {
+    loop {
+        std::thread::sleep(Duration::from_millis(10));
+    }
+}
+
+#[test(timeout = "100ms", serial)]
+fn setup_hangs(stuck: &Vec<u8>) {
+    println!("never got {:?}", stuck);
+}
+
+#[test(serial)]
+fn runs_next(connection: &Vec<u8>) {
+    std::thread::sleep(Duration::from_millis(200));
+    println!("next test got {:?}", connection);
+}
+
+fn main() {
+    let report = std::env::temp_dir().join(format!("satchel-timeout-{}.xml", std::process::id()));
+    let args = RunnerArgs::parse_from([
+        "timeout".as_ref(),
+        "--test-threads".as_ref(),
+        "1".as_ref(),
+        "--junit-xml".as_ref(),
+        report.as_os_str(),
+    ]);
+
+    let passed = test_runner::run_tests(satchel::get_tests!(), args);
+    let xml = std::fs::read_to_string(&report).unwrap();
+    let _ = std::fs::remove_file(&report);
+
+    assert!(!passed, "the hung test must fail the run");
+    assert!(xml.contains("Test timed out after 100ms"), "{}", xml);
+    assert!(
+        xml.contains("Test timeout::setup_hangs timed out after 100ms; its process was killed"),
+        "{}",
+        xml
+    );
+    let start = xml
+        .find("name=\"runs_next\"")
+        .expect("runs_next is reported");
+    let runs_next = &xml[start..start + xml[start..].find("</testcase>").unwrap()];
+    assert!(runs_next.contains("next test got [1, 2, 3]"), "{}", xml);
+    assert!(!runs_next.contains("still using"), "{}", xml);
+    println!("timeout: the failure above is expected");
+}